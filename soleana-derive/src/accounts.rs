@@ -0,0 +1,97 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, GenericArgument, PathArguments, Type};
+
+/// Returns `T` if `ty` is a `wrapper<T>`, such as an `Option<T>` or a `Vec<T>`.
+pub(crate) fn type_argument<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    match args.args.first() {
+        Some(GenericArgument::Type(argument)) if segment.ident == wrapper => Some(argument),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `ty` is an `Option<..>`.
+pub(crate) fn is_option(ty: &Type) -> bool {
+    type_argument(ty, "Option").is_some()
+}
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Accounts can only be derived for structs",
+        ));
+    };
+
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Accounts can only be derived for structs with named fields",
+        ));
+    };
+
+    let (from_accounts, labeled): (Vec<_>, Vec<_>) = fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().expect("named field");
+            let label = ident.to_string();
+
+            if is_option(&field.ty) {
+                (
+                    quote! {
+                        #ident: accounts.next().filter(|account| *account != program_id)
+                    },
+                    quote! {
+                        if let Some(pubkey) = self.#ident {
+                            labeled.push(::soleana::value::LabeledAccount::new(#label, pubkey));
+                        }
+                    },
+                )
+            } else {
+                (
+                    quote! {
+                        #ident: accounts
+                            .next()
+                            .ok_or(::soleana::error::SoleanaError::NotEnoughAccounts)?
+                    },
+                    quote! {
+                        labeled.push(::soleana::value::LabeledAccount::new(#label, self.#ident));
+                    },
+                )
+            }
+        })
+        .unzip();
+
+    Ok(quote! {
+        impl ::soleana::programs::context::Accounts for #name {
+            #[allow(unused_variables, unused_mut)]
+            fn from_accounts(
+                program_id: ::soleana::types::Pubkey,
+                accounts: &[::soleana::types::Pubkey],
+            ) -> ::soleana::error::SoleanaResult<Self> {
+                let mut accounts = accounts.iter().copied();
+                Ok(Self {
+                    #(#from_accounts,)*
+                })
+            }
+
+            fn labeled(&self) -> ::std::vec::Vec<::soleana::value::LabeledAccount> {
+                let mut labeled = ::std::vec::Vec::new();
+                #(#labeled)*
+                labeled
+            }
+        }
+    })
+}
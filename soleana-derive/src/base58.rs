@@ -0,0 +1,24 @@
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Decodes a base58 string, `None` if it holds characters outside of the alphabet.
+pub(crate) fn decode(input: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    for c in input.bytes() {
+        let mut carry = ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let leading_zeros = input.bytes().take_while(|&c| c == b'1').count();
+    bytes.extend(std::iter::repeat_n(0, leading_zeros));
+    bytes.reverse();
+    Some(bytes)
+}
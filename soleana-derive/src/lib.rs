@@ -0,0 +1,56 @@
+//! Derive macros generating [Soleana](https://docs.rs/soleana) program parsers.
+//!
+//! `#[derive(Program)]` implements `Program` and `ProgramInstructions` for an enum of
+//! instructions, `#[derive(Accounts)]` implements `Accounts` for a struct of named accounts.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Accounts derive implementation.
+mod accounts;
+
+/// Program derive implementation.
+mod program;
+
+/// Base58 decoding of program ids.
+mod base58;
+
+/// Implements `Program` and `ProgramInstructions` for an enum of instructions.
+///
+/// ```ignore
+/// #[derive(Debug, Clone, PartialEq, Eq, Program)]
+/// #[program(id = "6LtLpnUFNByNXLyCoK9wA2MykKAmQNZKBdY8s47dehDc", name = "Kamino")]
+/// pub enum KaminoInstructions {
+///     #[anchor("deposit")]
+///     Deposit {
+///         token_max_a: u64,
+///         token_max_b: u64,
+///         #[accounts]
+///         accounts: KaminoDepositAccounts,
+///     },
+/// }
+/// ```
+///
+/// Every variant needs a `#[discriminator(..)]` with the bytes prefixing its data, or an
+/// `#[anchor("name")]` for Anchor instructions. The fields are decoded in order with `Decode` and
+/// must use all the data, the field marked `#[accounts]` is built from the instruction accounts
+/// with `Accounts`. Data matching no discriminator is left unparsed.
+#[proc_macro_derive(Program, attributes(program, discriminator, anchor, accounts))]
+pub fn derive_program(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    program::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Accounts` for a struct of named accounts.
+///
+/// Fields are either `Pubkey` or `Option<Pubkey>` for optional accounts, in the order the
+/// program expects them.
+#[proc_macro_derive(Accounts)]
+pub fn derive_accounts(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    accounts::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,305 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    punctuated::Punctuated, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit, LitStr,
+    Token, Type, Variant,
+};
+
+use crate::{accounts::type_argument, base58};
+
+/// Arguments of the `#[program(..)]` attribute.
+struct ProgramArgs {
+    id: [u8; 32],
+    name: String,
+}
+
+fn parse_program_args(input: &DeriveInput) -> syn::Result<ProgramArgs> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("program"))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(&input.ident, "missing `#[program(id = \"..\")]` attribute")
+        })?;
+
+    let mut id = None;
+    let mut name = input.ident.to_string();
+
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("id") {
+            let value: LitStr = meta.value()?.parse()?;
+            let bytes = base58::decode(&value.value())
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .ok_or_else(|| syn::Error::new_spanned(&value, "invalid base58 program id"))?;
+            id = Some(bytes);
+            Ok(())
+        } else if meta.path.is_ident("name") {
+            name = meta.value()?.parse::<LitStr>()?.value();
+            Ok(())
+        } else {
+            Err(meta.error("unknown program argument, expected `id` or `name`"))
+        }
+    })?;
+
+    Ok(ProgramArgs {
+        id: id.ok_or_else(|| syn::Error::new_spanned(attr, "missing program `id`"))?,
+        name,
+    })
+}
+
+/// Flattens `#[discriminator(1, 2)]` and `#[discriminator([1, 2])]` into the bytes.
+fn parse_discriminator(attr: &Attribute) -> syn::Result<Vec<u8>> {
+    let exprs = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+
+    let exprs: Vec<Expr> = match exprs.first() {
+        Some(Expr::Array(array)) if exprs.len() == 1 => array.elems.iter().cloned().collect(),
+        _ => exprs.into_iter().collect(),
+    };
+
+    exprs
+        .iter()
+        .map(|expr| match expr {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(int), ..
+            }) => int.base10_parse::<u8>(),
+            _ => Err(syn::Error::new_spanned(expr, "expected a byte literal")),
+        })
+        .collect()
+}
+
+/// Returns an expression of the bytes prefixing the data of `variant`.
+fn variant_discriminator(variant: &Variant) -> syn::Result<TokenStream> {
+    for attr in &variant.attrs {
+        if attr.path().is_ident("discriminator") {
+            let bytes = parse_discriminator(attr)?;
+            return Ok(quote! { [#(#bytes),*] });
+        }
+
+        if attr.path().is_ident("anchor") {
+            let name: LitStr = attr.parse_args()?;
+            return Ok(quote! { const { ::soleana::anchor::anchor_discriminator(#name) } });
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        &variant.ident,
+        "missing `#[discriminator(..)]` or `#[anchor(\"..\")]` attribute",
+    ))
+}
+
+/// Returns `true` if `ty` is a `Pubkey` or a `[u8; 32]`.
+fn is_pubkey(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Pubkey"),
+        Type::Array(array) => {
+            matches!(&*array.elem, Type::Path(elem) if elem.path.is_ident("u8"))
+                && matches!(&array.len, Expr::Lit(ExprLit { lit: Lit::Int(len), .. })
+                    if len.base10_parse::<usize>().ok() == Some(32))
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if `ty` is a pubkey, or an `Option`, a `Vec` or an array holding pubkeys.
+fn holds_pubkey(ty: &Type) -> bool {
+    if is_pubkey(ty) {
+        return true;
+    }
+
+    match ty {
+        Type::Array(array) => holds_pubkey(&array.elem),
+        _ => type_argument(ty, "Option")
+            .or_else(|| type_argument(ty, "Vec"))
+            .is_some_and(holds_pubkey),
+    }
+}
+
+/// Returns an expression converting `value`, a reference to a `ty`, into a `Value`.
+///
+/// `Pubkey` is an alias of `[u8; 32]`, so `ToValue` can't tell them apart from byte arrays:
+/// pubkeys are converted here instead, at any depth of `Option`, `Vec` and arrays.
+fn to_value(ty: &Type, value: TokenStream) -> TokenStream {
+    if is_pubkey(ty) {
+        return quote! { ::soleana::value::Value::Pubkey(*#value) };
+    }
+
+    if holds_pubkey(ty) {
+        if let Some(inner) = type_argument(ty, "Option") {
+            let inner = to_value(inner, quote! { value });
+            return quote! {
+                ::soleana::value::Value::Option(
+                    #value.as_ref().map(|value| ::std::boxed::Box::new(#inner))
+                )
+            };
+        }
+
+        let inner = match ty {
+            Type::Array(array) => &*array.elem,
+            _ => type_argument(ty, "Vec").expect("checked by holds_pubkey"),
+        };
+        let inner = to_value(inner, quote! { value });
+        return quote! {
+            ::soleana::value::Value::Array(#value.iter().map(|value| #inner).collect())
+        };
+    }
+
+    quote! { ::soleana::value::ToValue::to_value(#value) }
+}
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let ProgramArgs {
+        id,
+        name: program_name,
+    } = parse_program_args(&input)?;
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Program can't be derived for generic enums",
+        ));
+    }
+
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Program can only be derived for enums",
+        ));
+    };
+
+    let mut parse_arms = Vec::new();
+    let mut name_arms = Vec::new();
+    let mut accounts_arms = Vec::new();
+    let mut fields_arms = Vec::new();
+
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let variant_name = ident.to_string();
+        let discriminator = variant_discriminator(variant)?;
+
+        let fields = match &variant.fields {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "Program variants must have named fields or no fields",
+                ))
+            }
+        };
+
+        let is_accounts = |field: &syn::Field| {
+            field
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("accounts"))
+        };
+        let accounts_field = fields.iter().find(|f| is_accounts(f)).map(|f| &f.ident);
+        let args: Vec<_> = fields.iter().filter(|f| !is_accounts(f)).collect();
+
+        let decode_args = args.iter().map(|field| {
+            let field_ident = &field.ident;
+            quote! { #field_ident: ::soleana::programs::decode::Decode::decode(&mut data)? }
+        });
+        let decode_accounts = accounts_field.map(|field_ident| {
+            quote! {
+                #field_ident: ::soleana::programs::context::Accounts::from_accounts(
+                    program_id,
+                    &<Self as ::soleana::programs::Program>::match_accounts(
+                        ix_accounts,
+                        accounts,
+                    )?,
+                )?
+            }
+        });
+        let construct = if matches!(variant.fields, Fields::Unit) {
+            quote! { Self::#ident }
+        } else {
+            quote! { Self::#ident { #(#decode_args,)* #decode_accounts } }
+        };
+
+        parse_arms.push(quote! {
+            if let Some(data) = data.strip_prefix(&#discriminator[..]) {
+                let mut data = ::soleana::programs::decode::DataCursor::new(data);
+                let instruction = #construct;
+                data.finish()?;
+                return Ok(instruction);
+            }
+        });
+
+        name_arms.push(quote! { Self::#ident { .. } => #variant_name });
+
+        accounts_arms.push(match accounts_field {
+            Some(field_ident) => quote! {
+                Self::#ident { #field_ident, .. } =>
+                    ::soleana::programs::context::Accounts::labeled(#field_ident)
+            },
+            None => quote! { Self::#ident { .. } => ::std::vec::Vec::new() },
+        });
+
+        let arg_idents = args.iter().map(|field| &field.ident);
+        let arg_values = args.iter().map(|field| {
+            let field_ident = &field.ident;
+            let label = field_ident.as_ref().expect("named field").to_string();
+            let value = to_value(&field.ty, quote! { #field_ident });
+            quote! { (#label.to_string(), #value) }
+        });
+        fields_arms.push(quote! {
+            Self::#ident { #(#arg_idents,)* .. } =>
+                ::soleana::value::Value::Struct(::std::vec![#(#arg_values),*])
+        });
+    }
+
+    Ok(quote! {
+        impl ::soleana::programs::Program for #name {
+            fn program_id() -> ::soleana::types::Pubkey {
+                [#(#id),*]
+            }
+
+            fn name() -> &'static str {
+                #program_name
+            }
+
+            type Instructions = Self;
+
+            #[allow(unused_mut, unused_variables, clippy::ptr_arg)]
+            fn parse_instruction(
+                program_id: ::soleana::types::Pubkey,
+                ix_accounts: &::std::vec::Vec<u8>,
+                data: &[u8],
+                accounts: &[::soleana::types::Pubkey],
+            ) -> ::soleana::error::SoleanaResult<Self> {
+                #(#parse_arms)*
+                Err(::soleana::error::SoleanaError::UnknownInstruction)
+            }
+        }
+
+        impl ::soleana::programs::ProgramInstructions for #name {
+            fn program_name(&self) -> &str {
+                #program_name
+            }
+
+            fn instruction_name(&self) -> &str {
+                match self {
+                    #(#name_arms,)*
+                }
+            }
+
+            fn accounts(&self) -> ::std::vec::Vec<::soleana::value::LabeledAccount> {
+                match self {
+                    #(#accounts_arms,)*
+                }
+            }
+
+            fn fields(&self) -> ::soleana::value::Value {
+                match self {
+                    #(#fields_arms,)*
+                }
+            }
+        }
+    })
+}
@@ -1,17 +1,40 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::{hint::black_box, thread};
 
 use soleana::TransactionsParser;
 
+const TRANSACTION: &str = "01c79cc65469fdfcc8fb10150150e33c73220b976162999d1e38a81176de3aaf90af7f39eacbd261932badd65c3551cdac3f1e60585e2c92e3b52f117bac35750680010002040e7698886e86cd5f4faf3ab562b70f97736ffd2c62eaa7bfe194a2021a82d97cbf971b59108b5b85a04fb093f1e21b4e3fd4c4c8f487dd09b95752769f0dd8c300000000000000000000000000000000000000000000000000000000000000000306466fe5211732ffecadba72c39be7bc8ce5bbc5f7126b2c439b3a400000000124ad783cd3b62be732496acc325d8337e80f1fa06d278a9b534f28fe60a4740203000502e8030000020200010c02000000401f00000000000000";
+
+const THREADS: usize = 4;
+const TRANSACTIONS_PER_THREAD: usize = 1_000;
+
 fn criterion_benchmark(c: &mut Criterion) {
-    let mut parser = TransactionsParser::new();
-    c.bench_function("parse_transaction", |b| { 
+    let parser = TransactionsParser::new();
+    c.bench_function("parse_transaction", |b| {
+        b.iter(|| {
+            let _ = black_box(parser.parse_transaction(black_box(TRANSACTION)));
+        });
+    });
+
+    // A single parser shared by every thread, without any locking on our side.
+    let mut group = c.benchmark_group("parse_transaction_multi_threaded");
+    group.throughput(Throughput::Elements(
+        (THREADS * TRANSACTIONS_PER_THREAD) as u64,
+    ));
+    group.bench_function(format!("{THREADS}_threads"), |b| {
         b.iter(|| {
-            black_box(parser.parse_transaction(
-                "01c79cc65469fdfcc8fb10150150e33c73220b976162999d1e38a81176de3aaf90af7f39eacbd261932badd65c3551cdac3f1e60585e2c92e3b52f117bac35750680010002040e7698886e86cd5f4faf3ab562b70f97736ffd2c62eaa7bfe194a2021a82d97cbf971b59108b5b85a04fb093f1e21b4e3fd4c4c8f487dd09b95752769f0dd8c300000000000000000000000000000000000000000000000000000000000000000306466fe5211732ffecadba72c39be7bc8ce5bbc5f7126b2c439b3a400000000124ad783cd3b62be732496acc325d8337e80f1fa06d278a9b534f28fe60a4740203000502e8030000020200010c02000000401f00000000000000"
-            ));
+            thread::scope(|s| {
+                for _ in 0..THREADS {
+                    s.spawn(|| {
+                        for _ in 0..TRANSACTIONS_PER_THREAD {
+                            let _ = black_box(parser.parse_transaction(black_box(TRANSACTION)));
+                        }
+                    });
+                }
+            });
         });
     });
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
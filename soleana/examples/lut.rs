@@ -2,7 +2,7 @@ use solana_client::rpc_client::RpcClient;
 use solana_pubkey::Pubkey;
 use soleana::TransactionsParser;
 fn main() {
-    let mut parser = TransactionsParser::new();
+    let parser = TransactionsParser::new();
 
     // Register a lut fetch function to the parser.
     // This function will only be used when a lut is not found in the parser's registry.
@@ -1,9 +1,11 @@
 use solana_client::rpc_client::RpcClient;
 use solana_pubkey::Pubkey as SolanaPubkey;
-use soleana::{prelude::program_impl::*, TransactionsParser};
+use soleana::{anchor::anchor_discriminator, prelude::program_impl::*, TransactionsParser};
 
 struct Kamino;
 
+const DEPOSIT: [u8; 8] = anchor_discriminator("deposit");
+
 impl Program for Kamino {
     fn program_id() -> Pubkey {
         [
@@ -20,11 +22,13 @@ impl Program for Kamino {
         data: &[u8],
         accounts: &[Pubkey],
     ) -> SoleanaResult<KaminoInstructions> {
-        let accs = Kamino::match_accounts(ix_accounts_indexes, accounts);
-        match data[0..8] {
-            [0xf2, 0x23, 0xc6, 0x89, 0x52, 0xe1, 0xf2, 0xb6] => Ok(KaminoInstructions::Deposit {
-                token_max_a: u64::from_le_bytes(data[8..16].try_into().unwrap()),
-                token_max_b: u64::from_le_bytes(data[16..24].try_into().unwrap()),
+        let accs = Kamino::match_accounts(ix_accounts_indexes, accounts)?;
+        let mut data = DataCursor::new(data);
+        match data.read_array()? {
+            DEPOSIT if accs.len() < 23 => Err(SoleanaError::NotEnoughAccounts),
+            DEPOSIT => Ok(KaminoInstructions::Deposit {
+                token_max_a: data.read_u64()?,
+                token_max_b: data.read_u64()?,
                 accounts: KaminoDepositAccounts {
                     user: accs[0],
                     strategy: accs[1],
@@ -51,12 +55,12 @@ impl Program for Kamino {
                     instruction_sysvar_account: accs[22],
                 },
             }),
-            _ => Err(SoleanaError::InvalidInstruction),
+            _ => Err(SoleanaError::UnknownInstruction),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KaminoInstructions {
     Deposit {
         token_max_a: u64,
@@ -65,9 +69,38 @@ pub enum KaminoInstructions {
     },
 }
 
-impl ProgramInstructions for KaminoInstructions {}
+impl ProgramInstructions for KaminoInstructions {
+    fn program_name(&self) -> &str {
+        "Kamino"
+    }
+
+    fn instruction_name(&self) -> &str {
+        match self {
+            KaminoInstructions::Deposit { .. } => "Deposit",
+        }
+    }
+
+    fn accounts(&self) -> Vec<LabeledAccount> {
+        match self {
+            KaminoInstructions::Deposit { accounts, .. } => accounts.labeled(),
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            KaminoInstructions::Deposit {
+                token_max_a,
+                token_max_b,
+                ..
+            } => Value::Struct(vec![
+                ("token_max_a".to_string(), Value::U64(*token_max_a)),
+                ("token_max_b".to_string(), Value::U64(*token_max_b)),
+            ]),
+        }
+    }
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KaminoDepositAccounts {
     pub user: Pubkey,
     pub strategy: Pubkey,
@@ -94,8 +127,41 @@ pub struct KaminoDepositAccounts {
     pub instruction_sysvar_account: Pubkey,
 }
 
+impl KaminoDepositAccounts {
+    fn labeled(&self) -> Vec<LabeledAccount> {
+        vec![
+            LabeledAccount::new("user", self.user),
+            LabeledAccount::new("strategy", self.strategy),
+            LabeledAccount::new("global_config", self.global_config),
+            LabeledAccount::new("pool", self.pool),
+            LabeledAccount::new("position", self.position),
+            LabeledAccount::new("tick_array_lower", self.tick_array_lower),
+            LabeledAccount::new("tick_array_upper", self.tick_array_upper),
+            LabeledAccount::new("token_a_vault", self.token_a_vault),
+            LabeledAccount::new("token_b_vault", self.token_b_vault),
+            LabeledAccount::new("base_vault_authority", self.base_vault_authority),
+            LabeledAccount::new("token_a_ata", self.token_a_ata),
+            LabeledAccount::new("token_b_ata", self.token_b_ata),
+            LabeledAccount::new("token_a_mint", self.token_a_mint),
+            LabeledAccount::new("token_b_mint", self.token_b_mint),
+            LabeledAccount::new("user_shares_ata", self.user_shares_ata),
+            LabeledAccount::new("shares_mint", self.shares_mint),
+            LabeledAccount::new("shares_mint_authority", self.shares_mint_authority),
+            LabeledAccount::new("scope_prices", self.scope_prices),
+            LabeledAccount::new("token_infos", self.token_infos),
+            LabeledAccount::new("token_program", self.token_program),
+            LabeledAccount::new("token_a_token_program", self.token_a_token_program),
+            LabeledAccount::new("token_b_token_program", self.token_b_token_program),
+            LabeledAccount::new(
+                "instruction_sysvar_account",
+                self.instruction_sysvar_account,
+            ),
+        ]
+    }
+}
+
 fn main() {
-    let mut parser = TransactionsParser::new();
+    let parser = TransactionsParser::new();
     parser.register_program::<Kamino>();
 
     let lut = fetch_lut(&[
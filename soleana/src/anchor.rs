@@ -0,0 +1,45 @@
+use crate::sha256;
+
+/// Size of an Anchor discriminator.
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// The 8 bytes prefixing the data of Anchor instructions, accounts and events.
+pub type Discriminator = [u8; DISCRIMINATOR_LEN];
+
+/// Returns `sha256("<namespace>:<name>")[..8]`, the discriminator scheme used by Anchor.
+pub const fn sighash(namespace: &str, name: &str) -> Discriminator {
+    let hash = sha256::hashv(&[namespace.as_bytes(), b":", name.as_bytes()]);
+    let mut discriminator = [0u8; DISCRIMINATOR_LEN];
+    let mut i = 0;
+    while i < DISCRIMINATOR_LEN {
+        discriminator[i] = hash[i];
+        i += 1;
+    }
+    discriminator
+}
+
+/// Returns the discriminator of the Anchor instruction `name`, `sha256("global:<name>")[..8]`.
+///
+/// `name` is the snake case name of the instruction handler.
+///
+/// ```
+/// use soleana::anchor::anchor_discriminator;
+///
+/// const DEPOSIT: [u8; 8] = anchor_discriminator("deposit");
+/// assert_eq!(DEPOSIT, [0xf2, 0x23, 0xc6, 0x89, 0x52, 0xe1, 0xf2, 0xb6]);
+/// ```
+pub const fn anchor_discriminator(name: &str) -> Discriminator {
+    sighash("global", name)
+}
+
+/// Returns the discriminator of the Anchor event `name`, `sha256("event:<name>")[..8]`.
+///
+/// `name` is the name of the event struct.
+pub const fn event_discriminator(name: &str) -> Discriminator {
+    sighash("event", name)
+}
+
+/// Returns the discriminator prefixing `data`, if it is long enough to carry one.
+pub fn discriminator_of(data: &[u8]) -> Option<Discriminator> {
+    data.get(..DISCRIMINATOR_LEN)?.try_into().ok()
+}
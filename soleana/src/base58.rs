@@ -0,0 +1,88 @@
+use crate::{
+    error::{SoleanaError, SoleanaResult},
+    types::Pubkey,
+};
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Decodes a base58 string, with the bitcoin alphabet used by Solana.
+pub fn decode(input: &str) -> SoleanaResult<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len());
+
+    for c in input.bytes() {
+        let mut carry = ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or(SoleanaError::InvalidBase58)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let leading_zeros = input.bytes().take_while(|&c| c == b'1').count();
+    bytes.extend(std::iter::repeat_n(0, leading_zeros));
+    bytes.reverse();
+    Ok(bytes)
+}
+
+/// Encodes `bytes` to a base58 string.
+pub fn encode(bytes: &[u8]) -> String {
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+
+    for &byte in bytes {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let leading_zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    std::iter::repeat_n(b'1', leading_zeros)
+        .chain(digits.iter().rev().map(|&digit| ALPHABET[digit as usize]))
+        .map(char::from)
+        .collect()
+}
+
+/// Decodes a base58 public key, rejecting any other length than 32 bytes.
+pub fn decode_pubkey(input: &str) -> SoleanaResult<Pubkey> {
+    decode(input)?
+        .try_into()
+        .map_err(|_| SoleanaError::InvalidBase58)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let compute_budget = "ComputeBudget111111111111111111111111111111";
+        let pubkey = decode_pubkey(compute_budget).unwrap();
+        assert_eq!(&pubkey[..4], &[3, 6, 70, 111]);
+        assert_eq!(encode(&pubkey), compute_budget);
+
+        assert_eq!(
+            decode_pubkey("11111111111111111111111111111111"),
+            Ok([0; 32])
+        );
+        assert_eq!(encode(&[0; 32]), "11111111111111111111111111111111");
+        assert_eq!(encode(&[0, 0, 1]), "112");
+        assert_eq!(decode("112"), Ok(vec![0, 0, 1]));
+        assert_eq!(encode(&[]), "");
+
+        assert_eq!(decode("0OIl"), Err(SoleanaError::InvalidBase58));
+        assert_eq!(decode_pubkey("1111"), Err(SoleanaError::InvalidBase58));
+    }
+}
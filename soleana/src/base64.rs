@@ -0,0 +1,84 @@
+use crate::error::{SoleanaError, SoleanaResult};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Returns the 6-bit value of a character of the standard alphabet.
+fn sextet(c: u8) -> SoleanaResult<u32> {
+    let value = match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return Err(SoleanaError::InvalidBase64),
+    };
+    Ok(value as u32)
+}
+
+/// Decodes a base64 string with the standard alphabet, as used by the RPC and the program logs.
+///
+/// The padding is optional.
+pub fn decode(input: &str) -> SoleanaResult<Vec<u8>> {
+    let input = input.trim_end_matches('=').as_bytes();
+    if input.len() % 4 == 1 {
+        return Err(SoleanaError::InvalidBase64);
+    }
+
+    let mut bytes = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut buffer = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            buffer |= sextet(c)? << (18 - 6 * i);
+        }
+
+        let decoded = buffer.to_be_bytes();
+        bytes.extend_from_slice(&decoded[1..chunk.len()]);
+    }
+
+    Ok(bytes)
+}
+
+/// Encodes `bytes` to a padded base64 string with the standard alphabet.
+pub fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let mut buffer = [0u8; 4];
+        buffer[1..=chunk.len()].copy_from_slice(chunk);
+        let buffer = u32::from_be_bytes(buffer);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(buffer >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xff\xfe\x00", "//4A"),
+        ] {
+            assert_eq!(encode(bytes), encoded);
+            assert_eq!(decode(encoded).as_deref(), Ok(bytes));
+        }
+
+        assert_eq!(decode("Zm8"), Ok(b"fo".to_vec()));
+        assert_eq!(decode("Z"), Err(SoleanaError::InvalidBase64));
+        assert_eq!(decode("Zm9v!"), Err(SoleanaError::InvalidBase64));
+    }
+}
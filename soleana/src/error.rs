@@ -1,12 +1,32 @@
+use crate::limits::Limit;
+
 pub type SoleanaResult<T> = Result<T, SoleanaError>;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum SoleanaError {
     InvalidHexString,
+    InvalidBase58,
+    InvalidBase64,
+    InvalidJson,
     NotEnoughBytes,
     CompactU16Overflow,
+    CompactU16NonCanonical,
+    UnsupportedVersion(u8),
 
     InvalidInstruction,
+    /// The parser doesn't know the instruction, a variant it doesn't implement or an unknown
+    /// discriminator.
+    UnknownInstruction,
+    TrailingBytes(usize),
+    InvalidAccountIndex,
+    NotEnoughAccounts,
+
+    MessageMismatch,
+    SignatureConflict(usize),
 
     NoLutFetchFnRegistered,
+
+    InvalidIdl(String),
+
+    LimitExceeded(Limit),
 }
@@ -0,0 +1,374 @@
+use std::{any::Any, marker::PhantomData};
+
+use crate::{
+    anchor::{self, Discriminator, DISCRIMINATOR_LEN},
+    base58, base64,
+    error::SoleanaResult,
+    programs::{decode::Decode, UNKNOWN_NAME},
+    registry::RegistryInner,
+    types::Pubkey,
+    value::Value,
+};
+
+/// The tag prefixing the data of the self-CPI instructions of Anchor's `emit_cpi!`, the event
+/// discriminator and data follow it.
+pub const EVENT_IX_TAG: Discriminator = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+/// A trait for events decoded by an [`EventDecoder`].
+///
+/// Decoded events are stored as `Box<dyn ProgramEvent>`, use [`downcast_ref`](#method.downcast_ref)
+/// or [`EmittedEvent::parsed_as`] to get the concrete type back.
+pub trait ProgramEvent: std::fmt::Debug + Any + DynEvent {
+    /// The name of the event.
+    fn event_name(&self) -> &str {
+        UNKNOWN_NAME
+    }
+
+    /// The fields of the event, as a [`Value::Struct`].
+    fn fields(&self) -> Value {
+        Value::empty()
+    }
+}
+
+/// Clone and equality for `dyn ProgramEvent`, implemented for every `Clone + Eq` type.
+pub trait DynEvent {
+    /// Clones the event into a new box.
+    fn clone_box(&self) -> Box<dyn ProgramEvent>;
+
+    /// Compares the event with an event of any type, different types are never equal.
+    fn dyn_eq(&self, other: &dyn ProgramEvent) -> bool;
+}
+
+impl<T> DynEvent for T
+where
+    T: ProgramEvent + Clone + Eq,
+{
+    fn clone_box(&self) -> Box<dyn ProgramEvent> {
+        Box::new(self.clone())
+    }
+
+    fn dyn_eq(&self, other: &dyn ProgramEvent) -> bool {
+        other.downcast_ref::<T>() == Some(self)
+    }
+}
+
+impl Clone for Box<dyn ProgramEvent> {
+    fn clone(&self) -> Self {
+        self.as_ref().clone_box()
+    }
+}
+
+impl PartialEq for dyn ProgramEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn ProgramEvent {}
+
+impl dyn ProgramEvent {
+    /// Returns `true` if the event is of type `T`.
+    pub fn is<T: ProgramEvent>(&self) -> bool {
+        (self as &dyn Any).is::<T>()
+    }
+
+    /// Returns the event as `T`, if it is of type `T`.
+    pub fn downcast_ref<T: ProgramEvent>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref::<T>()
+    }
+}
+
+/// A hand-written Anchor event, decoded with borsh from the data following its discriminator.
+pub trait Event: ProgramEvent + Decode {
+    /// The name of the event struct, its discriminator is derived from it.
+    const NAME: &'static str;
+
+    /// The discriminator prefixing the data of the event, `sha256("event:<NAME>")[..8]`.
+    const DISCRIMINATOR: Discriminator = anchor::event_discriminator(Self::NAME);
+}
+
+/// A trait for decoders of the events of a program, registered per event discriminator.
+pub trait EventDecoder: Send + Sync {
+    /// Decodes an event from its data, discriminator included.
+    fn decode_event(&self, data: &[u8]) -> SoleanaResult<Box<dyn ProgramEvent>>;
+}
+
+impl<F> EventDecoder for F
+where
+    F: Fn(&[u8]) -> SoleanaResult<Box<dyn ProgramEvent>> + Send + Sync,
+{
+    fn decode_event(&self, data: &[u8]) -> SoleanaResult<Box<dyn ProgramEvent>> {
+        self(data)
+    }
+}
+
+/// Adapts a static [`Event`] to the [`EventDecoder`] trait.
+pub(crate) struct EventParser<E>(PhantomData<fn() -> E>);
+
+impl<E> EventParser<E> {
+    pub(crate) fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E: Event> EventDecoder for EventParser<E> {
+    fn decode_event(&self, data: &[u8]) -> SoleanaResult<Box<dyn ProgramEvent>> {
+        let event = E::from_slice(&data[DISCRIMINATOR_LEN..])?;
+        Ok(Box::new(event))
+    }
+}
+
+/// Where an [`EmittedEvent`] was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventSource {
+    /// A `Program data:` line of the logs, at this index.
+    Log(usize),
+    /// A self-CPI instruction of `emit_cpi!`.
+    Instruction,
+}
+
+/// An event emitted by a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmittedEvent {
+    /// The program that emitted the event.
+    pub program_id: Pubkey,
+    pub source: EventSource,
+    /// The name of the event, if a decoder is registered for its discriminator and names it.
+    pub name: Option<String>,
+    /// The decoded event, `None` without a registered decoder or when the decoder rejects it.
+    pub parsed: Option<Box<dyn ProgramEvent>>,
+    /// The data of the event, discriminator included.
+    ///
+    /// The text of the `Program data:` line when it isn't base64, as a program logging data
+    /// with another encoding.
+    pub data: Vec<u8>,
+}
+
+impl EmittedEvent {
+    /// Returns the decoded event as `T`, if it was decoded into a `T`.
+    pub fn parsed_as<T: ProgramEvent>(&self) -> Option<&T> {
+        self.parsed.as_deref()?.downcast_ref::<T>()
+    }
+}
+
+impl RegistryInner {
+    /// Decodes the event `data` emitted by `program_id`.
+    ///
+    /// Events without a registered decoder, or rejected by their decoder (an upgraded layout),
+    /// are kept undecoded so they don't hide the other events.
+    pub(crate) fn emitted_event(
+        &self,
+        program_id: Pubkey,
+        source: EventSource,
+        data: Vec<u8>,
+    ) -> EmittedEvent {
+        let decoder = anchor::discriminator_of(&data)
+            .and_then(|discriminator| self.event_decoders.get(&program_id)?.get(&discriminator));
+        let parsed = decoder.and_then(|decoder| decoder.decode_event(&data).ok());
+
+        EmittedEvent {
+            program_id,
+            source,
+            name: parsed
+                .as_ref()
+                .map(|parsed| parsed.event_name())
+                .filter(|&name| name != UNKNOWN_NAME)
+                .map(str::to_string),
+            parsed,
+            data,
+        }
+    }
+
+    /// Extracts the events of the `Program data:` lines of `logs`, each one attributed to the
+    /// program executing when it was logged.
+    pub(crate) fn log_events<S: AsRef<str>>(&self, logs: &[S]) -> SoleanaResult<Vec<EmittedEvent>> {
+        let mut stack: Vec<Pubkey> = Vec::new();
+        let mut events = Vec::new();
+
+        for (line, log) in logs.iter().enumerate() {
+            let log = log.as_ref();
+            if let Some(data) = log.strip_prefix("Program data: ") {
+                // Truncated logs may have lost the invocation of the program.
+                let Some(&program_id) = stack.last() else {
+                    continue;
+                };
+
+                // Every slice given to `sol_log_data` is encoded on its own.
+                let data = data
+                    .split(' ')
+                    .map(base64::decode)
+                    .collect::<SoleanaResult<Vec<_>>>()
+                    .map(|slices| slices.concat())
+                    .unwrap_or_else(|_| data.as_bytes().to_vec());
+                events.push(self.emitted_event(program_id, EventSource::Log(line), data));
+            } else if let Some(rest) = log.strip_prefix("Program ") {
+                let mut words = rest.split(' ');
+                match (words.next(), words.next()) {
+                    (Some(program_id), Some("invoke")) => {
+                        stack.push(base58::decode_pubkey(program_id)?)
+                    }
+                    (Some(_), Some("success" | "failed:")) => {
+                        stack.pop();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Extracts the event of an `emit_cpi!` instruction, `None` if the instruction isn't one.
+    pub(crate) fn cpi_event(&self, program_id: Pubkey, data: &[u8]) -> Option<EmittedEvent> {
+        data.strip_prefix(&EVENT_IX_TAG[..])
+            .map(|event| self.emitted_event(program_id, EventSource::Instruction, event.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{programs::decode::DataCursor, registry::Registry};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Swapped {
+        amount_in: u64,
+        amount_out: u64,
+    }
+
+    impl Decode for Swapped {
+        fn decode(data: &mut DataCursor) -> SoleanaResult<Self> {
+            Ok(Self {
+                amount_in: data.read()?,
+                amount_out: data.read()?,
+            })
+        }
+    }
+
+    impl ProgramEvent for Swapped {
+        fn event_name(&self) -> &str {
+            Self::NAME
+        }
+    }
+
+    impl Event for Swapped {
+        const NAME: &'static str = "Swapped";
+    }
+
+    fn swapped(amount_in: u64, amount_out: u64) -> Vec<u8> {
+        [
+            &Swapped::DISCRIMINATOR[..],
+            &amount_in.to_le_bytes(),
+            &amount_out.to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_log_events() {
+        let registry = Registry::builder().event::<Swapped>([0x0c; 32]).build();
+
+        let dex = base58::encode(&[0x0c; 32]);
+        let other = base58::encode(&[0x0d; 32]);
+        let logs = [
+            format!("Program {dex} invoke [1]"),
+            "Program log: Instruction: Swap".to_string(),
+            format!("Program {other} invoke [2]"),
+            format!("Program data: {}", base64::encode(&swapped(1, 2))),
+            format!("Program {other} success"),
+            format!("Program data: {}", base64::encode(&swapped(3, 4))),
+            format!("Program {dex} consumed 5000 of 200000 compute units"),
+            format!("Program {dex} success"),
+        ];
+
+        let events = registry.log_events(&logs).unwrap();
+        assert_eq!(events.len(), 2);
+
+        // The same event emitted by another program isn't decoded with the dex decoder.
+        assert_eq!(events[0].program_id, [0x0d; 32]);
+        assert_eq!(events[0].source, EventSource::Log(3));
+        assert_eq!(events[0].name, None);
+        assert!(events[0].parsed.is_none());
+
+        assert_eq!(events[1].program_id, [0x0c; 32]);
+        assert_eq!(events[1].name.as_deref(), Some("Swapped"));
+        assert_eq!(
+            events[1].parsed_as::<Swapped>(),
+            Some(&Swapped {
+                amount_in: 3,
+                amount_out: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_undecodable_log_events() {
+        let registry = Registry::builder().event::<Swapped>([0x0c; 32]).build();
+
+        let dex = base58::encode(&[0x0c; 32]);
+        let logs = [
+            format!("Program {dex} invoke [1]"),
+            format!("Program data: {}", base64::encode(&swapped(1, 2)[..12])),
+            "Program data: !".to_string(),
+            format!("Program data: {}", base64::encode(&swapped(3, 4))),
+            format!("Program {dex} success"),
+        ];
+
+        // The bad events are kept undecoded next to the good one.
+        let events = registry.log_events(&logs).unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(events[0].parsed.is_none());
+        assert_eq!(events[0].data, swapped(1, 2)[..12]);
+        assert!(events[1].parsed.is_none());
+        assert_eq!(events[1].data, b"!");
+        assert_eq!(
+            events[2].parsed_as::<Swapped>(),
+            Some(&Swapped {
+                amount_in: 3,
+                amount_out: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_cpi_events() {
+        let registry = Registry::builder().event::<Swapped>([0x0c; 32]).build();
+
+        let data = [&EVENT_IX_TAG[..], &swapped(5, 6)].concat();
+        let event = registry.cpi_event([0x0c; 32], &data).unwrap();
+        assert_eq!(event.source, EventSource::Instruction);
+        assert_eq!(event.data, swapped(5, 6));
+        assert_eq!(
+            event.parsed_as::<Swapped>(),
+            Some(&Swapped {
+                amount_in: 5,
+                amount_out: 6
+            })
+        );
+
+        assert_eq!(registry.cpi_event([0x0c; 32], &swapped(5, 6)), None);
+
+        // Events decoded without a name aren't named after the placeholder.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Opaque;
+
+        impl ProgramEvent for Opaque {}
+
+        registry.register_event_decoder(
+            [0x0d; 32],
+            Swapped::DISCRIMINATOR,
+            std::sync::Arc::new(|_: &[u8]| -> SoleanaResult<Box<dyn ProgramEvent>> {
+                Ok(Box::new(Opaque))
+            }),
+        );
+        let event = registry.cpi_event([0x0d; 32], &data).unwrap();
+        assert_eq!(event.name, None);
+        assert!(event.parsed_as::<Opaque>().is_some());
+
+        // A registered event with a bad layout is kept undecoded, like a bad instruction.
+        let truncated = [&EVENT_IX_TAG[..], &swapped(5, 6)[..12]].concat();
+        let event = registry.cpi_event([0x0c; 32], &truncated).unwrap();
+        assert!(event.parsed.is_none());
+        assert_eq!(event.data, swapped(5, 6)[..12]);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{
+    base58,
+    error::{SoleanaError, SoleanaResult},
+    json::Json,
+};
+
+use super::{
+    Idl, IdlAccount, IdlDiscriminator, IdlEnumVariant, IdlErrorCode, IdlEvent, IdlField, IdlFields,
+    IdlInstruction, IdlType, IdlTypeDef, IdlTypeDefTy,
+};
+
+/// Returns an [`SoleanaError::InvalidIdl`] describing what is wrong with the IDL.
+pub(crate) fn invalid(message: impl Into<String>) -> SoleanaError {
+    SoleanaError::InvalidIdl(message.into())
+}
+
+/// Returns the field `key` of `json`, which the IDL must have.
+pub(crate) fn field<'a>(json: &'a Json, key: &str) -> SoleanaResult<&'a Json> {
+    json.get(key)
+        .ok_or_else(|| invalid(format!("missing `{key}`")))
+}
+
+pub(crate) fn str_field<'a>(json: &'a Json, key: &str) -> SoleanaResult<&'a str> {
+    field(json, key)?
+        .as_str()
+        .ok_or_else(|| invalid(format!("`{key}` is not a string")))
+}
+
+/// Returns the array `key` of `json`, a missing array is empty.
+pub(crate) fn array_field<'a>(json: &'a Json, key: &str) -> SoleanaResult<&'a [Json]> {
+    match json.get(key) {
+        None | Some(Json::Null) => Ok(&[]),
+        Some(value) => value
+            .as_array()
+            .ok_or_else(|| invalid(format!("`{key}` is not an array"))),
+    }
+}
+
+/// Returns the flag `key` of `json`, a missing flag is `false`.
+pub(crate) fn bool_field(json: &Json, key: &str) -> bool {
+    json.get(key).and_then(Json::as_bool).unwrap_or(false)
+}
+
+pub(crate) fn bytes(json: &Json) -> SoleanaResult<Vec<u8>> {
+    json.as_array()
+        .ok_or_else(|| invalid("discriminator is not an array"))?
+        .iter()
+        .map(|byte| {
+            byte.as_u64()
+                .and_then(|byte| u8::try_from(byte).ok())
+                .ok_or_else(|| invalid("discriminator is not made of bytes"))
+        })
+        .collect()
+}
+
+/// Loads an Anchor 0.30+ IDL, the format with an `address` and explicit discriminators.
+pub fn from_json(json: &str) -> SoleanaResult<Idl> {
+    load(&Json::parse(json)?)
+}
+
+pub(crate) fn load(json: &Json) -> SoleanaResult<Idl> {
+    let name = str_field(field(json, "metadata")?, "name")?.to_string();
+
+    Ok(Idl {
+        address: Some(base58::decode_pubkey(str_field(json, "address")?)?),
+        name,
+        instructions: array_field(json, "instructions")?
+            .iter()
+            .map(instruction)
+            .collect::<SoleanaResult<_>>()?,
+        events: array_field(json, "events")?
+            .iter()
+            .map(|event| {
+                Ok(IdlEvent {
+                    name: str_field(event, "name")?.to_string(),
+                    discriminator: bytes(field(event, "discriminator")?)?
+                        .try_into()
+                        .map_err(|_| invalid("event discriminator is not 8 bytes"))?,
+                })
+            })
+            .collect::<SoleanaResult<_>>()?,
+        errors: errors(json, "msg")?,
+        types: array_field(json, "types")?
+            .iter()
+            .map(type_def)
+            .collect::<SoleanaResult<_>>()?,
+        anchor: true,
+    })
+}
+
+fn instruction(json: &Json) -> SoleanaResult<IdlInstruction> {
+    let discriminator = bytes(field(json, "discriminator")?)?;
+    let mut accounts = Vec::new();
+    flatten_accounts(
+        array_field(json, "accounts")?,
+        &ACCOUNT_FLAGS,
+        &str::to_string,
+        &mut accounts,
+    )?;
+
+    Ok(IdlInstruction {
+        name: str_field(json, "name")?.to_string(),
+        discriminators: vec![IdlDiscriminator::Bytes {
+            offset: 0,
+            bytes: discriminator.clone(),
+        }],
+        args_offset: discriminator.len(),
+        accounts,
+        args: array_field(json, "args")?
+            .iter()
+            .map(named_field)
+            .collect::<SoleanaResult<_>>()?,
+    })
+}
+
+/// Parses the `errors` of an IDL, `message` is the key of their message.
+pub(crate) fn errors(json: &Json, message: &str) -> SoleanaResult<Vec<IdlErrorCode>> {
+    array_field(json, "errors")?
+        .iter()
+        .map(|error| {
+            Ok(IdlErrorCode {
+                code: field(error, "code")?
+                    .as_u64()
+                    .and_then(|code| u32::try_from(code).ok())
+                    .ok_or_else(|| invalid("error `code` is not a u32"))?,
+                name: str_field(error, "name")?.to_string(),
+                msg: error
+                    .get(message)
+                    .and_then(Json::as_str)
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// The keys of the writable, signer and optional flags of the accounts.
+pub(crate) struct AccountFlags {
+    pub(crate) writable: &'static str,
+    pub(crate) signer: &'static str,
+    pub(crate) optional: &'static str,
+}
+
+const ACCOUNT_FLAGS: AccountFlags = AccountFlags {
+    writable: "writable",
+    signer: "signer",
+    optional: "optional",
+};
+
+/// Flattens the nested account groups of an instruction, in order, naming the accounts with
+/// `name`.
+pub(crate) fn flatten_accounts(
+    json: &[Json],
+    flags: &AccountFlags,
+    name: &dyn Fn(&str) -> String,
+    accounts: &mut Vec<IdlAccount>,
+) -> SoleanaResult<()> {
+    for account in json {
+        if let Some(Json::Array(group)) = account.get("accounts") {
+            flatten_accounts(group, flags, name, accounts)?;
+            continue;
+        }
+
+        accounts.push(IdlAccount {
+            name: name(str_field(account, "name")?),
+            writable: bool_field(account, flags.writable),
+            signer: bool_field(account, flags.signer),
+            optional: bool_field(account, flags.optional),
+        });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn named_field(json: &Json) -> SoleanaResult<IdlField> {
+    Ok(IdlField {
+        name: str_field(json, "name")?.to_string(),
+        ty: ty(field(json, "type")?)?,
+    })
+}
+
+/// Parses a type, both the 0.30 spelling (`pubkey`, `{"defined": {"name": ..}}`) and the legacy
+/// one (`publicKey`, `{"defined": ".."}`) are accepted.
+pub(crate) fn ty(json: &Json) -> SoleanaResult<IdlType> {
+    if let Some(name) = json.as_str() {
+        return Ok(match name {
+            "bool" => IdlType::Bool,
+            "u8" => IdlType::U8,
+            "u16" => IdlType::U16,
+            "u32" => IdlType::U32,
+            "u64" => IdlType::U64,
+            "u128" => IdlType::U128,
+            "u256" => IdlType::U256,
+            "i8" => IdlType::I8,
+            "i16" => IdlType::I16,
+            "i32" => IdlType::I32,
+            "i64" => IdlType::I64,
+            "i128" => IdlType::I128,
+            "i256" => IdlType::I256,
+            "f32" => IdlType::F32,
+            "f64" => IdlType::F64,
+            "bytes" => IdlType::Bytes,
+            "string" => IdlType::String,
+            "pubkey" | "publicKey" => IdlType::Pubkey,
+            _ => return Err(invalid(format!("unknown type `{name}`"))),
+        });
+    }
+
+    if let Some(inner) = json.get("option") {
+        return Ok(IdlType::Option(Box::new(ty(inner)?)));
+    }
+    if let Some(inner) = json.get("coption") {
+        return Ok(IdlType::COption(Box::new(ty(inner)?)));
+    }
+    if let Some(inner) = json.get("vec") {
+        return Ok(IdlType::Vec(Box::new(ty(inner)?)));
+    }
+    if let Some(array) = json.get("array") {
+        let [inner, len] = array.as_array().unwrap_or_default() else {
+            return Err(invalid("`array` is not a [type, length] pair"));
+        };
+        let len = len
+            .as_u64()
+            .ok_or_else(|| invalid("generic array lengths are not supported"))?;
+        return Ok(IdlType::Array(Box::new(ty(inner)?), len as usize));
+    }
+    if let Some(defined) = json.get("defined") {
+        let name = match defined.as_str() {
+            Some(name) => name,
+            None => str_field(defined, "name")?,
+        };
+        return Ok(IdlType::Defined(name.to_string()));
+    }
+
+    Err(invalid("unsupported type"))
+}
+
+/// Parses the fields of a struct or an enum variant, missing fields are an empty struct.
+pub(crate) fn fields(json: Option<&Json>) -> SoleanaResult<IdlFields> {
+    let fields = match json {
+        None | Some(Json::Null) => return Ok(IdlFields::Named(Vec::new())),
+        Some(fields) => fields
+            .as_array()
+            .ok_or_else(|| invalid("`fields` is not an array"))?,
+    };
+
+    if fields.iter().all(|field| field.get("name").is_some()) {
+        fields
+            .iter()
+            .map(named_field)
+            .collect::<SoleanaResult<_>>()
+            .map(IdlFields::Named)
+    } else {
+        fields
+            .iter()
+            .map(ty)
+            .collect::<SoleanaResult<_>>()
+            .map(IdlFields::Tuple)
+    }
+}
+
+pub(crate) fn type_def(json: &Json) -> SoleanaResult<IdlTypeDef> {
+    let def = field(json, "type")?;
+
+    let ty = match str_field(def, "kind")? {
+        "struct" => IdlTypeDefTy::Struct(fields(def.get("fields"))?),
+        "enum" => IdlTypeDefTy::Enum(
+            array_field(def, "variants")?
+                .iter()
+                .map(|variant| {
+                    Ok(IdlEnumVariant {
+                        name: str_field(variant, "name")?.to_string(),
+                        fields: fields(variant.get("fields"))?,
+                    })
+                })
+                .collect::<SoleanaResult<_>>()?,
+        ),
+        "type" => IdlTypeDefTy::Alias(ty(field(def, "alias")?)?),
+        kind => return Err(invalid(format!("unsupported type kind `{kind}`"))),
+    };
+
+    Ok(IdlTypeDef {
+        name: str_field(json, "name")?.to_string(),
+        ty,
+    })
+}
@@ -0,0 +1,598 @@
+use crate::{base58, base64, error::SoleanaResult, json::Json, shortvec};
+
+use super::{
+    anchor::{array_field, bool_field, errors, field, invalid, str_field},
+    legacy::to_snake_case,
+    Idl, IdlAccount, IdlDiscriminator, IdlEnumVariant, IdlField, IdlFields, IdlInstruction,
+    IdlLength, IdlType, IdlTypeDef, IdlTypeDefTy,
+};
+
+/// Loads a Codama IDL, the `rootNode` produced by Codama and Shank for programs without
+/// Anchor, such as the Metaplex ones.
+///
+/// Names of instructions, accounts and fields are converted to snake case, as with legacy
+/// Anchor IDLs. The arguments of an instruction are decoded from the start of its data, a
+/// discriminator argument leading the data is only matched, not decoded.
+pub fn from_json(json: &str) -> SoleanaResult<Idl> {
+    load(&Json::parse(json)?)
+}
+
+pub(crate) fn load(json: &Json) -> SoleanaResult<Idl> {
+    let program = field(json, "program")?;
+    if kind(program)? != "programNode" {
+        return Err(invalid("`program` is not a programNode"));
+    }
+
+    Ok(Idl {
+        address: Some(base58::decode_pubkey(str_field(program, "publicKey")?)?),
+        name: to_snake_case(str_field(program, "name")?),
+        instructions: array_field(program, "instructions")?
+            .iter()
+            .map(instruction)
+            .collect::<SoleanaResult<_>>()?,
+        events: Vec::new(),
+        errors: errors(program, "message")?,
+        types: array_field(program, "definedTypes")?
+            .iter()
+            .map(type_def)
+            .collect::<SoleanaResult<_>>()?,
+        anchor: false,
+    })
+}
+
+fn kind(json: &Json) -> SoleanaResult<&str> {
+    str_field(json, "kind")
+}
+
+fn instruction(json: &Json) -> SoleanaResult<IdlInstruction> {
+    let arguments = array_field(json, "arguments")?;
+    let mut args = arguments
+        .iter()
+        .map(named_field)
+        .collect::<SoleanaResult<Vec<_>>>()?;
+
+    let mut discriminators = Vec::new();
+    let mut args_offset = 0;
+    for discriminator in array_field(json, "discriminators")? {
+        let offset = discriminator
+            .get("offset")
+            .map(|offset| {
+                offset
+                    .as_u64()
+                    .ok_or_else(|| invalid("`offset` is not a number"))
+            })
+            .transpose()?
+            .unwrap_or(0) as usize;
+
+        match kind(discriminator)? {
+            "constantDiscriminatorNode" => {
+                let constant = field(discriminator, "constant")?;
+                discriminators.push(IdlDiscriminator::Bytes {
+                    offset,
+                    bytes: encode(field(constant, "type")?, field(constant, "value")?)?,
+                });
+            }
+            "fieldDiscriminatorNode" => {
+                let name = str_field(discriminator, "name")?;
+                let index = arguments
+                    .iter()
+                    .position(|argument| argument.get("name").and_then(Json::as_str) == Some(name))
+                    .ok_or_else(|| invalid(format!("unknown discriminator field `{name}`")))?;
+                let argument = &arguments[index];
+                let bytes = encode(field(argument, "type")?, field(argument, "defaultValue")?)?;
+
+                // The leading discriminator is skipped like the Anchor one, it would only
+                // repeat the name of the instruction among its fields.
+                if index == 0 && offset == 0 && args_offset == 0 {
+                    args_offset = bytes.len();
+                }
+                discriminators.push(IdlDiscriminator::Bytes { offset, bytes });
+            }
+            "sizeDiscriminatorNode" => {
+                let size = field(discriminator, "size")?
+                    .as_u64()
+                    .ok_or_else(|| invalid("`size` is not a number"))?;
+                discriminators.push(IdlDiscriminator::Size(size as usize));
+            }
+            kind => return Err(invalid(format!("unsupported discriminator `{kind}`"))),
+        }
+    }
+    if args_offset > 0 {
+        args.remove(0);
+    }
+
+    Ok(IdlInstruction {
+        name: to_snake_case(str_field(json, "name")?),
+        discriminators,
+        args_offset,
+        accounts: array_field(json, "accounts")?
+            .iter()
+            .map(|account| {
+                Ok(IdlAccount {
+                    name: to_snake_case(str_field(account, "name")?),
+                    writable: bool_field(account, "isWritable"),
+                    // `isSigner` may also be "either", the account isn't required to sign.
+                    signer: bool_field(account, "isSigner"),
+                    optional: bool_field(account, "isOptional"),
+                })
+            })
+            .collect::<SoleanaResult<_>>()?,
+        args,
+    })
+}
+
+fn named_field(json: &Json) -> SoleanaResult<IdlField> {
+    Ok(IdlField {
+        name: to_snake_case(str_field(json, "name")?),
+        ty: ty(field(json, "type")?)?,
+    })
+}
+
+/// Parses a `numberTypeNode`, only little-endian numbers are supported.
+fn number(json: &Json) -> SoleanaResult<IdlType> {
+    if kind(json)? != "numberTypeNode" {
+        return Err(invalid("expected a numberTypeNode"));
+    }
+    if json.get("endian").and_then(Json::as_str) == Some("be") {
+        return Err(invalid("big-endian numbers are not supported"));
+    }
+
+    Ok(match str_field(json, "format")? {
+        "u8" => IdlType::U8,
+        "u16" => IdlType::U16,
+        "u32" => IdlType::U32,
+        "u64" => IdlType::U64,
+        "u128" => IdlType::U128,
+        "i8" => IdlType::I8,
+        "i16" => IdlType::I16,
+        "i32" => IdlType::I32,
+        "i64" => IdlType::I64,
+        "i128" => IdlType::I128,
+        "f32" => IdlType::F32,
+        "f64" => IdlType::F64,
+        "shortU16" => IdlType::ShortU16,
+        format => return Err(invalid(format!("unknown number format `{format}`"))),
+    })
+}
+
+/// Parses a type node, the length of strings and bytes is given by their wrapping node.
+pub(crate) fn ty(json: &Json) -> SoleanaResult<IdlType> {
+    Ok(match kind(json)? {
+        "numberTypeNode" => number(json)?,
+        "amountTypeNode" | "dateTimeTypeNode" | "solAmountTypeNode" => {
+            number(field(json, "number")?)?
+        }
+        "booleanTypeNode" => match json.get("size").map(number).transpose()? {
+            None | Some(IdlType::U8) => IdlType::Bool,
+            Some(_) => return Err(invalid("only u8 booleans are supported")),
+        },
+        "publicKeyTypeNode" => IdlType::Pubkey,
+        "stringTypeNode" | "bytesTypeNode" => sized(json, IdlLength::Remainder)?,
+        "sizePrefixTypeNode" => {
+            let inner = field(json, "type")?;
+            match (
+                number(field(json, "prefix")?)?,
+                sized(inner, IdlLength::Remainder)?,
+            ) {
+                (IdlType::U32, IdlType::SizedString(_)) => IdlType::String,
+                (IdlType::U32, IdlType::SizedBytes(_)) => IdlType::Bytes,
+                (prefix, _) => sized(inner, IdlLength::Prefix(Box::new(prefix)))?,
+            }
+        }
+        "fixedSizeTypeNode" => {
+            let size = field(json, "size")?
+                .as_u64()
+                .ok_or_else(|| invalid("`size` is not a number"))?;
+            sized(field(json, "type")?, IdlLength::Fixed(size as usize))?
+        }
+        "optionTypeNode" => {
+            if bool_field(json, "fixed") {
+                return Err(invalid("fixed options are not supported"));
+            }
+
+            let item = Box::new(ty(field(json, "item")?)?);
+            match json.get("prefix").map(number).transpose()? {
+                None | Some(IdlType::U8) => IdlType::Option(item),
+                Some(IdlType::U32) => IdlType::COption(item),
+                Some(_) => return Err(invalid("only u8 and u32 option prefixes are supported")),
+            }
+        }
+        "arrayTypeNode" | "setTypeNode" => {
+            let item = Box::new(ty(field(json, "item")?)?);
+            let count = field(json, "count")?;
+            match kind(count)? {
+                "fixedCountNode" => {
+                    let len = field(count, "value")?
+                        .as_u64()
+                        .ok_or_else(|| invalid("`value` is not a number"))?;
+                    IdlType::Array(item, len as usize)
+                }
+                "prefixedCountNode" => match number(field(count, "prefix")?)? {
+                    IdlType::U32 => IdlType::Vec(item),
+                    prefix => IdlType::SizedVec(item, IdlLength::Prefix(Box::new(prefix))),
+                },
+                "remainderCountNode" => IdlType::SizedVec(item, IdlLength::Remainder),
+                kind => return Err(invalid(format!("unsupported count `{kind}`"))),
+            }
+        }
+        "tupleTypeNode" => IdlType::Tuple(
+            array_field(json, "items")?
+                .iter()
+                .map(ty)
+                .collect::<SoleanaResult<_>>()?,
+        ),
+        "definedTypeLinkNode" => IdlType::Defined(str_field(json, "name")?.to_string()),
+        kind => return Err(invalid(format!("unsupported type `{kind}`"))),
+    })
+}
+
+/// Parses a string or bytes node whose length is encoded by `length`.
+fn sized(json: &Json, length: IdlLength) -> SoleanaResult<IdlType> {
+    Ok(match kind(json)? {
+        "stringTypeNode" => match json.get("encoding").and_then(Json::as_str) {
+            None | Some("utf8") => IdlType::SizedString(length),
+            // Strings displayed in another encoding are bytes on chain.
+            Some(_) => IdlType::SizedBytes(length),
+        },
+        "bytesTypeNode" => IdlType::SizedBytes(length),
+        kind => return Err(invalid(format!("unsupported sized type `{kind}`"))),
+    })
+}
+
+/// Encodes the `value` node with the `ty` node, for the discriminators.
+fn encode(ty: &Json, value: &Json) -> SoleanaResult<Vec<u8>> {
+    Ok(match kind(ty)? {
+        "numberTypeNode" => {
+            let integer = field(value, "number")?
+                .as_u64()
+                .ok_or_else(|| invalid("`number` is not an unsigned integer"))?;
+            let width = match number(ty)? {
+                IdlType::ShortU16 => {
+                    let integer = u16::try_from(integer)
+                        .map_err(|_| invalid("`number` overflows its type"))?;
+                    return Ok(shortvec::encode(integer));
+                }
+                IdlType::U8 | IdlType::I8 => 1,
+                IdlType::U16 | IdlType::I16 => 2,
+                IdlType::U32 | IdlType::I32 => 4,
+                IdlType::U64 | IdlType::I64 => 8,
+                _ => return Err(invalid("unsupported discriminator number format")),
+            };
+            if width < 8 && integer >> (width * 8) != 0 {
+                return Err(invalid("`number` overflows its type"));
+            }
+            integer.to_le_bytes()[..width].to_vec()
+        }
+        "booleanTypeNode" => {
+            let boolean = field(value, "boolean")?
+                .as_bool()
+                .ok_or_else(|| invalid("`boolean` is not a boolean"))?;
+            vec![boolean as u8]
+        }
+        "bytesTypeNode" => {
+            let data = str_field(value, "data")?;
+            match str_field(value, "encoding")? {
+                "base16" => hex(data)?,
+                "base58" => base58::decode(data)?,
+                "base64" => base64::decode(data)?,
+                "utf8" => data.as_bytes().to_vec(),
+                encoding => return Err(invalid(format!("unknown encoding `{encoding}`"))),
+            }
+        }
+        "stringTypeNode" => str_field(value, "string")?.as_bytes().to_vec(),
+        "fixedSizeTypeNode" => {
+            let size = field(ty, "size")?
+                .as_u64()
+                .ok_or_else(|| invalid("`size` is not a number"))? as usize;
+            let mut bytes = encode(field(ty, "type")?, value)?;
+            if bytes.len() > size {
+                return Err(invalid("value longer than its fixed size"));
+            }
+            bytes.resize(size, 0);
+            bytes
+        }
+        "sizePrefixTypeNode" => {
+            let bytes = encode(field(ty, "type")?, value)?;
+            let len = Json::Number(bytes.len().to_string());
+            let mut prefixed = encode(
+                field(ty, "prefix")?,
+                &Json::Object(vec![("number".to_string(), len)]),
+            )?;
+            prefixed.extend(bytes);
+            prefixed
+        }
+        kind => return Err(invalid(format!("unsupported discriminator type `{kind}`"))),
+    })
+}
+
+fn hex(data: &str) -> SoleanaResult<Vec<u8>> {
+    if data.len() % 2 != 0 {
+        return Err(invalid("odd length base16 value"));
+    }
+
+    (0..data.len())
+        .step_by(2)
+        .map(|i| {
+            data.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| invalid("invalid base16 value"))
+        })
+        .collect()
+}
+
+fn struct_fields(json: &Json) -> SoleanaResult<Vec<IdlField>> {
+    if kind(json)? != "structTypeNode" {
+        return Err(invalid("expected a structTypeNode"));
+    }
+
+    array_field(json, "fields")?
+        .iter()
+        .map(named_field)
+        .collect()
+}
+
+fn type_def(json: &Json) -> SoleanaResult<IdlTypeDef> {
+    let def = field(json, "type")?;
+
+    let ty = match kind(def)? {
+        "structTypeNode" => IdlTypeDefTy::Struct(IdlFields::Named(struct_fields(def)?)),
+        "enumTypeNode" => {
+            if let Some(size) = def.get("size") {
+                if number(size)? != IdlType::U8 {
+                    return Err(invalid("only u8 enum sizes are supported"));
+                }
+            }
+
+            IdlTypeDefTy::Enum(
+                array_field(def, "variants")?
+                    .iter()
+                    .enumerate()
+                    .map(|(index, variant)| enum_variant(index, variant))
+                    .collect::<SoleanaResult<_>>()?,
+            )
+        }
+        _ => IdlTypeDefTy::Alias(ty(def)?),
+    };
+
+    Ok(IdlTypeDef {
+        name: str_field(json, "name")?.to_string(),
+        ty,
+    })
+}
+
+fn enum_variant(index: usize, json: &Json) -> SoleanaResult<IdlEnumVariant> {
+    // Variants are decoded by position, explicit discriminators must agree with it.
+    if let Some(discriminator) = json.get("discriminator").and_then(Json::as_u64) {
+        if discriminator != index as u64 {
+            return Err(invalid(
+                "out of order enum discriminators are not supported",
+            ));
+        }
+    }
+
+    let fields = match kind(json)? {
+        "enumEmptyVariantTypeNode" => IdlFields::Named(Vec::new()),
+        "enumStructVariantTypeNode" => IdlFields::Named(struct_fields(field(json, "struct")?)?),
+        "enumTupleVariantTypeNode" => match ty(field(json, "tuple")?)? {
+            IdlType::Tuple(types) => IdlFields::Tuple(types),
+            _ => return Err(invalid("expected a tupleTypeNode")),
+        },
+        kind => return Err(invalid(format!("unsupported enum variant `{kind}`"))),
+    };
+
+    Ok(IdlEnumVariant {
+        name: str_field(json, "name")?.to_string(),
+        fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::SoleanaError,
+        idl::{
+            tests::{instruction, transaction},
+            DynamicInstruction, IdlErrorCode,
+        },
+        registry::Registry,
+        value::{LabeledAccount, Value},
+        TransactionsParser,
+    };
+
+    const METADATA_IDL: &str = include_str!("../../fixtures/idl/metadata_codama.json");
+
+    #[test]
+    fn test_load_codama_idl() {
+        let idl = Idl::from_json(METADATA_IDL).unwrap();
+        assert_eq!(idl.address, Some([0x0c; 32]));
+        assert_eq!(idl.name, "token_metadata");
+
+        let create = &idl.instructions[0];
+        assert_eq!(create.name, "create_metadata_account_v3");
+        assert_eq!(
+            create.discriminators,
+            [IdlDiscriminator::Bytes {
+                offset: 0,
+                bytes: vec![33]
+            }]
+        );
+        assert_eq!(create.args_offset, 1);
+        assert_eq!(
+            create
+                .args
+                .iter()
+                .map(|arg| arg.name.as_str())
+                .collect::<Vec<_>>(),
+            ["data", "is_mutable", "collection_details"]
+        );
+        assert_eq!(create.accounts[2].name, "mint_authority");
+        assert!(create.accounts[2].signer);
+        assert!(create.accounts[3].optional);
+
+        // A constant discriminator is matched, its argument is still decoded.
+        assert_eq!(idl.instructions[1].args_offset, 0);
+        assert_eq!(idl.instructions[1].args.len(), 3);
+        assert!(!idl.instructions[1].accounts[0].signer);
+        assert_eq!(
+            idl.instructions[2].discriminators,
+            [IdlDiscriminator::Size(9)]
+        );
+
+        assert_eq!(
+            idl.errors,
+            [IdlErrorCode {
+                code: 0,
+                name: "instructionUnpackError".to_string(),
+                msg: Some("Failed to unpack instruction data".to_string()),
+            }]
+        );
+
+        let IdlTypeDefTy::Struct(IdlFields::Named(fields)) = &idl.types[0].ty else {
+            panic!("dataV2 is a struct");
+        };
+        assert_eq!(fields[0].ty, IdlType::String);
+        assert_eq!(
+            fields[1].ty,
+            IdlType::SizedString(IdlLength::Prefix(Box::new(IdlType::U8)))
+        );
+        assert_eq!(
+            fields[3].ty,
+            IdlType::Option(Box::new(IdlType::Vec(Box::new(IdlType::Defined(
+                "creator".to_string()
+            )))))
+        );
+
+        assert_eq!(
+            from_json(
+                r#"{"kind": "rootNode", "program": {"kind": "programNode", "name": "x",
+                "publicKey": "11111111111111111111111111111111", "instructions": [{"name": "y",
+                "arguments": [{"name": "z", "type": {"kind": "mapTypeNode"}}]}]}}"#
+            ),
+            Err(SoleanaError::InvalidIdl(
+                "unsupported type `mapTypeNode`".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_with_codama_idl() {
+        let parser = TransactionsParser::with_registry(Registry::builder().build());
+        assert_eq!(parser.register_idl(METADATA_IDL), Ok([0x0c; 32]));
+
+        let create = instruction(
+            "00010203",
+            &("21".to_owned()
+                + "030000004e4654"
+                + "024e46"
+                + "f401"
+                + "0101000000"
+                + &"0a".repeat(32)
+                + "0164"
+                + "00"
+                + "01"
+                + "01000a00000000000000"),
+        );
+        let set_fee = instruction("00", "f00dac026869");
+        let ping = instruction("", "070500000000000000");
+        let batch = instruction(
+            "",
+            &("2801".to_owned() + &"0d".repeat(32) + "020405" + "01000200"),
+        );
+
+        let parsed = parser
+            .parse_transaction(&transaction(&[create, set_fee, ping, batch]))
+            .unwrap();
+        let instructions = &parsed.message.instructions;
+
+        let create = instructions[0].parsed_as::<DynamicInstruction>().unwrap();
+        assert_eq!(create.program_name, "token_metadata");
+        assert_eq!(create.instruction_name, "create_metadata_account_v3");
+        assert_eq!(
+            create.accounts,
+            [
+                LabeledAccount::new("metadata", [0x0a; 32]),
+                LabeledAccount::new("mint", [0x0b; 32]),
+                LabeledAccount::new("mint_authority", [0x0d; 32]),
+            ]
+        );
+        assert_eq!(
+            create.fields,
+            Value::Struct(vec![
+                (
+                    "data".to_string(),
+                    Value::Struct(vec![
+                        ("name".to_string(), Value::String("NFT".to_string())),
+                        ("symbol".to_string(), Value::String("NF".to_string())),
+                        ("seller_fee_basis_points".to_string(), Value::U16(500)),
+                        (
+                            "creators".to_string(),
+                            Value::Option(Some(Box::new(Value::Array(vec![Value::Struct(vec![
+                                ("address".to_string(), Value::Pubkey([0x0a; 32])),
+                                ("verified".to_string(), Value::Bool(true)),
+                                ("share".to_string(), Value::U8(100)),
+                            ])]))))
+                        ),
+                        (
+                            "token_standard".to_string(),
+                            Value::Enum("nonFungible".to_string(), Box::new(Value::empty()))
+                        ),
+                    ])
+                ),
+                ("is_mutable".to_string(), Value::Bool(true)),
+                (
+                    "collection_details".to_string(),
+                    Value::Option(Some(Box::new(Value::Enum(
+                        "v1".to_string(),
+                        Box::new(Value::Struct(vec![("size".to_string(), Value::U64(10))]))
+                    ))))
+                ),
+            ])
+        );
+
+        let set_fee = instructions[1].parsed_as::<DynamicInstruction>().unwrap();
+        assert_eq!(set_fee.instruction_name, "set_fee");
+        assert_eq!(
+            set_fee.fields,
+            Value::Struct(vec![
+                ("tag".to_string(), Value::Bytes(vec![0xf0, 0x0d])),
+                ("fee".to_string(), Value::U16(300)),
+                ("memo".to_string(), Value::String("hi".to_string())),
+            ])
+        );
+
+        let ping = instructions[2].parsed_as::<DynamicInstruction>().unwrap();
+        assert_eq!(ping.instruction_name, "ping");
+        assert_eq!(ping.fields.get("lamports"), Some(&Value::U64(5)));
+
+        let batch = instructions[3].parsed_as::<DynamicInstruction>().unwrap();
+        assert_eq!(batch.instruction_name, "batch");
+        assert_eq!(
+            batch.fields,
+            Value::Struct(vec![
+                (
+                    "pair".to_string(),
+                    Value::Array(vec![Value::U8(1), Value::Pubkey([0x0d; 32])])
+                ),
+                (
+                    "ids".to_string(),
+                    Value::Array(vec![Value::U8(4), Value::U8(5)])
+                ),
+                (
+                    "amounts".to_string(),
+                    Value::Array(vec![Value::U16(1), Value::U16(2)])
+                ),
+            ])
+        );
+
+        // A remainder array can't end in the middle of an item.
+        let truncated = instruction(
+            "",
+            &("2801".to_owned() + &"0d".repeat(32) + "00" + "010002"),
+        );
+        let parsed = parser
+            .parse_transaction(&transaction(&[truncated]))
+            .unwrap();
+        assert!(parsed.message.instructions[0].parsed.is_none());
+    }
+}
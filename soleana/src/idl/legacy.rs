@@ -0,0 +1,262 @@
+use crate::{
+    anchor::{anchor_discriminator, event_discriminator, DISCRIMINATOR_LEN},
+    base58,
+    error::SoleanaResult,
+    json::Json,
+    types::Pubkey,
+};
+
+use super::{
+    anchor::{
+        array_field, errors, fields, flatten_accounts, named_field, str_field, type_def,
+        AccountFlags,
+    },
+    Idl, IdlDiscriminator, IdlEvent, IdlField, IdlFields, IdlInstruction, IdlTypeDef, IdlTypeDefTy,
+};
+
+/// Converts a camel case IDL name to snake case, as Anchor does to derive discriminators.
+///
+/// `initializeVault` becomes `initialize_vault`, `setAuthorityV2` becomes `set_authority_v2`.
+pub fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+
+    snake
+}
+
+/// Loads a legacy (pre-0.30) Anchor IDL, bound to `program_id`.
+///
+/// Legacy IDLs carry no address (only sometimes a `metadata.address`), so the program id is
+/// given explicitly, `None` falls back to the metadata. Names are converted to snake case and
+/// the discriminators are derived from the instruction names.
+pub fn from_json(json: &str, program_id: Option<Pubkey>) -> SoleanaResult<Idl> {
+    let mut idl = load(&Json::parse(json)?)?;
+    if program_id.is_some() {
+        idl.address = program_id;
+    }
+    Ok(idl)
+}
+
+pub(crate) fn load(json: &Json) -> SoleanaResult<Idl> {
+    let address = json
+        .get("metadata")
+        .and_then(|metadata| metadata.get("address"))
+        .and_then(Json::as_str)
+        .map(base58::decode_pubkey)
+        .transpose()?;
+
+    let mut types = array_field(json, "types")?
+        .iter()
+        .map(type_def)
+        .collect::<SoleanaResult<Vec<_>>>()?;
+    for def in &mut types {
+        match &mut def.ty {
+            IdlTypeDefTy::Struct(fields) => rename_fields(fields),
+            IdlTypeDefTy::Enum(variants) => variants
+                .iter_mut()
+                .for_each(|variant| rename_fields(&mut variant.fields)),
+            IdlTypeDefTy::Alias(_) => {}
+        }
+    }
+
+    // The fields of legacy events are inlined, they become types as in the 0.30 format.
+    let mut events = Vec::new();
+    for event in array_field(json, "events")? {
+        let name = str_field(event, "name")?;
+        let mut fields = fields(event.get("fields"))?;
+        rename_fields(&mut fields);
+
+        events.push(IdlEvent {
+            name: name.to_string(),
+            discriminator: event_discriminator(name),
+        });
+        types.push(IdlTypeDef {
+            name: name.to_string(),
+            ty: IdlTypeDefTy::Struct(fields),
+        });
+    }
+
+    Ok(Idl {
+        address,
+        name: str_field(json, "name")?.to_string(),
+        instructions: array_field(json, "instructions")?
+            .iter()
+            .map(instruction)
+            .collect::<SoleanaResult<_>>()?,
+        events,
+        errors: errors(json, "msg")?,
+        types,
+        anchor: true,
+    })
+}
+
+fn rename_fields(fields: &mut IdlFields) {
+    if let IdlFields::Named(fields) = fields {
+        for field in fields {
+            field.name = to_snake_case(&field.name);
+        }
+    }
+}
+
+fn instruction(json: &Json) -> SoleanaResult<IdlInstruction> {
+    let name = to_snake_case(str_field(json, "name")?);
+
+    let mut accounts = Vec::new();
+    flatten_accounts(
+        array_field(json, "accounts")?,
+        &ACCOUNT_FLAGS,
+        &to_snake_case,
+        &mut accounts,
+    )?;
+
+    Ok(IdlInstruction {
+        discriminators: vec![IdlDiscriminator::Bytes {
+            offset: 0,
+            bytes: anchor_discriminator(&name).to_vec(),
+        }],
+        args_offset: DISCRIMINATOR_LEN,
+        name,
+        accounts,
+        args: array_field(json, "args")?
+            .iter()
+            .map(|arg| {
+                let IdlField { name, ty } = named_field(arg)?;
+                Ok(IdlField {
+                    name: to_snake_case(&name),
+                    ty,
+                })
+            })
+            .collect::<SoleanaResult<_>>()?,
+    })
+}
+
+/// The legacy spelling of the account flags.
+const ACCOUNT_FLAGS: AccountFlags = AccountFlags {
+    writable: "isMut",
+    signer: "isSigner",
+    optional: "isOptional",
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::SoleanaError,
+        idl::{
+            tests::{instruction, transaction},
+            DynamicInstruction,
+        },
+        registry::Registry,
+        value::{LabeledAccount, Value},
+        TransactionsParser,
+    };
+
+    const VAULT_IDL: &str = include_str!("../../fixtures/idl/vault.json");
+    const LEGACY_VAULT_IDL: &str = include_str!("../../fixtures/idl/vault_legacy.json");
+
+    #[test]
+    fn test_load_legacy_idl() {
+        let idl = from_json(LEGACY_VAULT_IDL, Some([0x0c; 32])).unwrap();
+        assert_eq!(idl.address, Some([0x0c; 32]));
+        assert_eq!(idl.instructions[1].name, "withdraw_all");
+        assert_eq!(
+            idl.instructions[1].discriminators,
+            [IdlDiscriminator::Bytes {
+                offset: 0,
+                bytes: vec![0x60, 0xf6, 0xa6, 0x82, 0xe5, 0x32, 0x2b, 0x46]
+            }]
+        );
+        assert_eq!(idl.instructions[0].accounts[2].name, "vault_authority");
+        assert!(idl.instructions[0].accounts[0].signer);
+        assert!(idl.instructions[0].accounts[1].writable);
+        assert!(idl.instructions[0].accounts[3].optional);
+
+        // Normalized into the same model as the 0.30 IDL of the same program.
+        let modern = Idl::from_json(VAULT_IDL).unwrap();
+        assert_eq!(
+            idl.instructions[0].accounts,
+            modern.instructions[0].accounts
+        );
+        assert_eq!(idl.instructions[0].args, modern.instructions[0].args);
+        assert_eq!(idl.types, modern.types);
+        assert_eq!(idl.events, modern.events);
+        assert_eq!(idl.errors, modern.errors);
+    }
+
+    #[test]
+    fn test_parse_with_legacy_idl() {
+        let parser = TransactionsParser::with_registry(Registry::builder().build());
+        assert_eq!(
+            parser.register_idl(LEGACY_VAULT_IDL),
+            Err(SoleanaError::InvalidIdl("missing `address`".to_string()))
+        );
+        parser
+            .register_idl_with_id(LEGACY_VAULT_IDL, [0x0c; 32])
+            .unwrap();
+
+        let deposit = instruction(
+            "00010203",
+            &("f223c68952e1f2b6".to_owned() + "e803000000000000" + "00" + "00" + "1e0000000000"),
+        );
+        let withdraw_all = instruction("0001", &("60f6a682e5322b46".to_owned() + &"0d".repeat(32)));
+
+        let transaction = parser
+            .parse_transaction(&transaction(&[deposit, withdraw_all]))
+            .unwrap();
+        let instructions = &transaction.message.instructions;
+
+        let deposit = instructions[0].parsed_as::<DynamicInstruction>().unwrap();
+        assert_eq!(
+            deposit.fields.get("config"),
+            Some(&Value::Struct(vec![
+                ("fee_bps".to_string(), Value::U16(30)),
+                ("owner".to_string(), Value::Option(None)),
+            ]))
+        );
+        assert_eq!(
+            deposit.fields.get("side"),
+            Some(&Value::Enum("Bid".to_string(), Box::new(Value::empty())))
+        );
+
+        let withdraw_all = instructions[1].parsed_as::<DynamicInstruction>().unwrap();
+        assert_eq!(withdraw_all.instruction_name, "withdraw_all");
+        assert_eq!(
+            withdraw_all.accounts,
+            [
+                LabeledAccount::new("user", [0x0a; 32]),
+                LabeledAccount::new("vault", [0x0b; 32]),
+            ]
+        );
+        assert_eq!(
+            withdraw_all.fields.get("destination"),
+            Some(&Value::Pubkey([0x0d; 32]))
+        );
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        for (name, snake) in [
+            ("deposit", "deposit"),
+            ("initializeVault", "initialize_vault"),
+            ("setAuthorityV2", "set_authority_v2"),
+            ("HTTPServer", "http_server"),
+            ("already_snake", "already_snake"),
+        ] {
+            assert_eq!(to_snake_case(name), snake);
+        }
+    }
+}
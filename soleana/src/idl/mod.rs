@@ -0,0 +1,851 @@
+use std::collections::HashMap;
+
+use crate::{
+    anchor::{discriminator_of, Discriminator, DISCRIMINATOR_LEN},
+    error::{SoleanaError, SoleanaResult},
+    events::{EventDecoder, ProgramEvent},
+    json::Json,
+    programs::{context::ParseContext, decode::DataCursor, InstructionParser, ProgramInstructions},
+    types::Pubkey,
+    value::{LabeledAccount, Value},
+};
+
+/// Anchor module implements the loader of Anchor 0.30+ IDLs.
+pub mod anchor;
+
+/// Legacy module implements the loader of pre-0.30 Anchor IDLs.
+pub mod legacy;
+
+/// Codama module implements the loader of Codama IDLs, the format of Shank and native programs.
+pub mod codama;
+
+/// Shank module implements the loader of the legacy-shaped IDLs generated by Shank.
+pub mod shank;
+
+/// Maximum nesting of defined types, deeper (or self-referencing) types are rejected.
+const MAX_TYPE_DEPTH: usize = 64;
+
+/// A program interface loaded from an IDL, the source of an [`IdlParser`].
+///
+/// Every supported IDL format is normalized into this model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Idl {
+    /// The program id the IDL was published for, if the IDL carries it.
+    pub address: Option<Pubkey>,
+    pub name: String,
+    pub instructions: Vec<IdlInstruction>,
+    pub events: Vec<IdlEvent>,
+    pub errors: Vec<IdlErrorCode>,
+    pub types: Vec<IdlTypeDef>,
+    /// Whether the program is built with Anchor, and so raises the framework errors of Anchor.
+    pub anchor: bool,
+}
+
+impl Idl {
+    /// Loads an IDL from its JSON, detecting its format.
+    ///
+    /// Anchor 0.30+ IDLs have an `address` and a `metadata.spec`, legacy ones don't and may
+    /// only be registered with an explicit program id. Shank IDLs look like legacy ones but have
+    /// a `metadata.origin` of `shank`. Codama IDLs are a `rootNode`.
+    pub fn from_json(json: &str) -> SoleanaResult<Idl> {
+        let json = Json::parse(json)?;
+        if json.get("kind").and_then(Json::as_str) == Some("rootNode") {
+            return codama::load(&json);
+        }
+        if shank::is_shank(&json) {
+            return shank::load(&json);
+        }
+
+        let spec = json
+            .get("metadata")
+            .and_then(|metadata| metadata.get("spec"));
+        match (json.get("address"), spec) {
+            (None, None) => legacy::load(&json),
+            _ => anchor::load(&json),
+        }
+    }
+}
+
+/// An instruction of an [`Idl`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlInstruction {
+    pub name: String,
+    /// The conditions identifying the instruction from its data, all of them must hold.
+    pub discriminators: Vec<IdlDiscriminator>,
+    /// The number of bytes skipped before decoding the arguments, the Anchor discriminator.
+    pub args_offset: usize,
+    pub accounts: Vec<IdlAccount>,
+    pub args: Vec<IdlField>,
+}
+
+impl IdlInstruction {
+    /// Returns whether `data` meets every discriminator of the instruction.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.discriminators
+            .iter()
+            .all(|discriminator| discriminator.matches(data))
+    }
+
+    /// Returns how many bytes of the data the discriminators pin down, used to prefer the most
+    /// specific instruction when several match.
+    fn specificity(&self) -> (usize, usize) {
+        self.discriminators
+            .iter()
+            .fold(
+                (0, 0),
+                |(bytes, sizes), discriminator| match discriminator {
+                    IdlDiscriminator::Bytes { bytes: value, .. } => (bytes + value.len(), sizes),
+                    IdlDiscriminator::Size(_) => (bytes, sizes + 1),
+                },
+            )
+    }
+}
+
+/// A condition identifying an [`IdlInstruction`] from its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlDiscriminator {
+    /// The data holds `bytes` at `offset`.
+    Bytes { offset: usize, bytes: Vec<u8> },
+    /// The data is exactly this many bytes long.
+    Size(usize),
+}
+
+impl IdlDiscriminator {
+    pub fn matches(&self, data: &[u8]) -> bool {
+        match self {
+            IdlDiscriminator::Bytes { offset, bytes } => data
+                .get(*offset..)
+                .is_some_and(|data| data.starts_with(bytes)),
+            IdlDiscriminator::Size(size) => data.len() == *size,
+        }
+    }
+}
+
+/// An event of an [`Idl`], its fields are the struct of [`Idl::types`] with the same name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlEvent {
+    pub name: String,
+    pub discriminator: Discriminator,
+}
+
+/// A custom error of an [`Idl`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlErrorCode {
+    pub code: u32,
+    pub name: String,
+    pub msg: Option<String>,
+}
+
+/// An account of an [`IdlInstruction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlAccount {
+    pub name: String,
+    pub writable: bool,
+    pub signer: bool,
+    /// Missing optional accounts are set to the program id, as Anchor does.
+    pub optional: bool,
+}
+
+/// A named field of an instruction, a struct or an enum variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlField {
+    pub name: String,
+    pub ty: IdlType,
+}
+
+/// The type of a field, decoded with the borsh layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    I256,
+    F32,
+    F64,
+    /// A compact u16, the shortvec encoding of the transaction format.
+    ShortU16,
+    /// A u32 length followed by the bytes.
+    Bytes,
+    String,
+    Pubkey,
+    /// A 0/1 tag followed by the value.
+    Option(Box<IdlType>),
+    /// A u32 0/1 tag followed by the value, the `COption` of the token programs.
+    COption(Box<IdlType>),
+    /// A u32 length followed by the items.
+    Vec(Box<IdlType>),
+    Array(Box<IdlType>, usize),
+    /// Bytes whose length is encoded by the [`IdlLength`].
+    SizedBytes(IdlLength),
+    /// A utf-8 string whose length in bytes is encoded by the [`IdlLength`].
+    SizedString(IdlLength),
+    /// Items whose count is encoded by the [`IdlLength`].
+    SizedVec(Box<IdlType>, IdlLength),
+    Tuple(Vec<IdlType>),
+    /// A type of [`Idl::types`].
+    Defined(String),
+}
+
+/// How the length of [`IdlType::SizedBytes`], [`IdlType::SizedString`] and
+/// [`IdlType::SizedVec`] is encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlLength {
+    /// Prefixed by the length, an unsigned integer type.
+    Prefix(Box<IdlType>),
+    Fixed(usize),
+    /// Up to the end of the data.
+    Remainder,
+}
+
+/// A type defined by an [`Idl`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlTypeDef {
+    pub name: String,
+    pub ty: IdlTypeDefTy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlTypeDefTy {
+    Struct(IdlFields),
+    /// A u8 variant index followed by the fields of the variant.
+    Enum(Vec<IdlEnumVariant>),
+    Alias(IdlType),
+}
+
+/// The fields of a struct or an enum variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlFields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlType>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlEnumVariant {
+    pub name: String,
+    pub fields: IdlFields,
+}
+
+/// A parser built at runtime from an [`Idl`].
+///
+/// Instructions are parsed into a [`DynamicInstruction`], with field-named arguments and
+/// labeled accounts.
+///
+/// Like the events, the arguments must use all the data, trailing bytes are an error. Instructions
+/// missing from the IDL, or whose accounts and data don't match it (a stale IDL), are left
+/// unparsed.
+#[derive(Debug, Clone)]
+pub struct IdlParser {
+    idl: Idl,
+    types: HashMap<String, IdlTypeDef>,
+}
+
+impl IdlParser {
+    pub fn new(idl: Idl) -> Self {
+        let types = idl
+            .types
+            .iter()
+            .map(|ty| (ty.name.clone(), ty.clone()))
+            .collect();
+
+        Self { idl, types }
+    }
+
+    /// Returns the IDL the parser was built from.
+    pub fn idl(&self) -> &Idl {
+        &self.idl
+    }
+
+    /// Finds the instruction whose discriminators match `data`, preferring the most specific
+    /// one.
+    fn instruction(&self, data: &[u8]) -> Option<&IdlInstruction> {
+        self.idl
+            .instructions
+            .iter()
+            .filter(|instruction| instruction.matches(data))
+            .max_by_key(|instruction| instruction.specificity())
+    }
+
+    /// Reads the length encoded by `length`, `None` for the rest of the data.
+    fn decode_length(
+        &self,
+        length: &IdlLength,
+        data: &mut DataCursor,
+        depth: usize,
+    ) -> SoleanaResult<Option<usize>> {
+        let prefix = match length {
+            IdlLength::Prefix(prefix) => prefix,
+            IdlLength::Fixed(len) => return Ok(Some(*len)),
+            IdlLength::Remainder => return Ok(None),
+        };
+
+        let len = match self.decode(prefix, data, depth + 1)? {
+            Value::U8(len) => len as u64,
+            Value::U16(len) => len as u64,
+            Value::U32(len) => len as u64,
+            Value::U64(len) => len,
+            _ => return Err(SoleanaError::InvalidInstruction),
+        };
+        usize::try_from(len)
+            .map(Some)
+            .map_err(|_| SoleanaError::NotEnoughBytes)
+    }
+
+    fn decode_sized_bytes(
+        &self,
+        length: &IdlLength,
+        data: &mut DataCursor,
+        depth: usize,
+    ) -> SoleanaResult<Vec<u8>> {
+        let len = match self.decode_length(length, data, depth)? {
+            Some(len) => len,
+            None => data.remaining(),
+        };
+        data.read_bytes(len).map(<[u8]>::to_vec)
+    }
+
+    fn decode_named(
+        &self,
+        fields: &[IdlField],
+        data: &mut DataCursor,
+        depth: usize,
+    ) -> SoleanaResult<Value> {
+        fields
+            .iter()
+            .map(|field| Ok((field.name.clone(), self.decode(&field.ty, data, depth)?)))
+            .collect::<SoleanaResult<_>>()
+            .map(Value::Struct)
+    }
+
+    fn decode_fields(
+        &self,
+        fields: &IdlFields,
+        data: &mut DataCursor,
+        depth: usize,
+    ) -> SoleanaResult<Value> {
+        match fields {
+            IdlFields::Named(fields) => self.decode_named(fields, data, depth),
+            IdlFields::Tuple(types) => types
+                .iter()
+                .map(|ty| self.decode(ty, data, depth))
+                .collect::<SoleanaResult<_>>()
+                .map(Value::Array),
+        }
+    }
+
+    fn decode(&self, ty: &IdlType, data: &mut DataCursor, depth: usize) -> SoleanaResult<Value> {
+        if depth > MAX_TYPE_DEPTH {
+            return Err(SoleanaError::InvalidInstruction);
+        }
+
+        Ok(match ty {
+            IdlType::Bool => Value::Bool(data.read_bool()?),
+            IdlType::U8 => Value::U8(data.read_u8()?),
+            IdlType::U16 => Value::U16(data.read_u16()?),
+            IdlType::U32 => Value::U32(data.read_u32()?),
+            IdlType::U64 => Value::U64(data.read_u64()?),
+            IdlType::U128 => Value::U128(data.read_u128()?),
+            IdlType::I8 => Value::I8(data.read_i8()?),
+            IdlType::I16 => Value::I16(data.read_i16()?),
+            IdlType::I32 => Value::I32(data.read_i32()?),
+            IdlType::I64 => Value::I64(data.read_i64()?),
+            IdlType::I128 => Value::I128(data.read_i128()?),
+            IdlType::U256 | IdlType::I256 => Value::Bytes(data.read_bytes(32)?.to_vec()),
+            IdlType::F32 => Value::F32(data.read_f32()?),
+            IdlType::F64 => Value::F64(data.read_f64()?),
+            IdlType::ShortU16 => Value::U16(data.read_compact_u16()?),
+            IdlType::Bytes => {
+                let len = data.read_u32()? as usize;
+                Value::Bytes(data.read_bytes(len)?.to_vec())
+            }
+            IdlType::String => Value::String(data.read_string()?),
+            IdlType::Pubkey => Value::Pubkey(data.read_pubkey()?),
+            IdlType::Option(inner) => match data.read_u8()? {
+                0 => Value::Option(None),
+                1 => Value::Option(Some(Box::new(self.decode(inner, data, depth + 1)?))),
+                _ => return Err(SoleanaError::InvalidInstruction),
+            },
+            IdlType::COption(inner) => match data.read_u32()? {
+                0 => Value::Option(None),
+                1 => Value::Option(Some(Box::new(self.decode(inner, data, depth + 1)?))),
+                _ => return Err(SoleanaError::InvalidInstruction),
+            },
+            IdlType::Vec(inner) => {
+                let len = data.read_u32()? as usize;
+                // Every item takes at least a byte, a longer length can't be valid.
+                if len > data.remaining() {
+                    return Err(SoleanaError::NotEnoughBytes);
+                }
+
+                (0..len)
+                    .map(|_| self.decode(inner, data, depth + 1))
+                    .collect::<SoleanaResult<_>>()
+                    .map(Value::Array)?
+            }
+            IdlType::Array(inner, len) => (0..*len)
+                .map(|_| self.decode(inner, data, depth + 1))
+                .collect::<SoleanaResult<_>>()
+                .map(Value::Array)?,
+            IdlType::SizedBytes(length) => {
+                Value::Bytes(self.decode_sized_bytes(length, data, depth)?)
+            }
+            IdlType::SizedString(length) => {
+                let bytes = self.decode_sized_bytes(length, data, depth)?;
+                Value::String(
+                    String::from_utf8(bytes).map_err(|_| SoleanaError::InvalidInstruction)?,
+                )
+            }
+            IdlType::SizedVec(inner, length) => match self.decode_length(length, data, depth)? {
+                Some(len) => {
+                    if len > data.remaining() {
+                        return Err(SoleanaError::NotEnoughBytes);
+                    }
+
+                    (0..len)
+                        .map(|_| self.decode(inner, data, depth + 1))
+                        .collect::<SoleanaResult<_>>()
+                        .map(Value::Array)?
+                }
+                None => {
+                    let mut items = Vec::new();
+                    while !data.is_empty() {
+                        let remaining = data.remaining();
+                        items.push(self.decode(inner, data, depth + 1)?);
+                        // An item reading nothing would never reach the end of the data.
+                        if data.remaining() == remaining {
+                            return Err(SoleanaError::InvalidInstruction);
+                        }
+                    }
+                    Value::Array(items)
+                }
+            },
+            IdlType::Tuple(types) => types
+                .iter()
+                .map(|ty| self.decode(ty, data, depth + 1))
+                .collect::<SoleanaResult<_>>()
+                .map(Value::Array)?,
+            IdlType::Defined(name) => {
+                let def = self
+                    .types
+                    .get(name)
+                    .ok_or(SoleanaError::InvalidInstruction)?;
+                match &def.ty {
+                    IdlTypeDefTy::Struct(fields) => self.decode_fields(fields, data, depth + 1)?,
+                    IdlTypeDefTy::Enum(variants) => {
+                        let variant = variants
+                            .get(data.read_variant()? as usize)
+                            .ok_or(SoleanaError::InvalidInstruction)?;
+                        Value::Enum(
+                            variant.name.clone(),
+                            Box::new(self.decode_fields(&variant.fields, data, depth + 1)?),
+                        )
+                    }
+                    IdlTypeDefTy::Alias(ty) => self.decode(ty, data, depth + 1)?,
+                }
+            }
+        })
+    }
+}
+
+impl EventDecoder for IdlParser {
+    fn decode_event(&self, data: &[u8]) -> SoleanaResult<Box<dyn ProgramEvent>> {
+        let discriminator = discriminator_of(data).ok_or(SoleanaError::NotEnoughBytes)?;
+        let event = self
+            .idl
+            .events
+            .iter()
+            .find(|event| event.discriminator == discriminator)
+            .ok_or(SoleanaError::InvalidInstruction)?;
+
+        let mut cursor = DataCursor::new(&data[DISCRIMINATOR_LEN..]);
+        let fields = self.decode(&IdlType::Defined(event.name.clone()), &mut cursor, 0)?;
+        cursor.finish()?;
+
+        Ok(Box::new(DynamicEvent {
+            event_name: event.name.clone(),
+            fields,
+            data: data.to_vec(),
+        }))
+    }
+}
+
+impl InstructionParser for IdlParser {
+    fn name(&self) -> Option<&str> {
+        Some(&self.idl.name)
+    }
+
+    fn parse_with_context(
+        &self,
+        context: &ParseContext,
+    ) -> SoleanaResult<Box<dyn ProgramInstructions>> {
+        let instruction = self
+            .instruction(context.data())
+            .ok_or(SoleanaError::UnknownInstruction)?;
+
+        let args = context
+            .data()
+            .get(instruction.args_offset..)
+            .ok_or(SoleanaError::NotEnoughBytes)?;
+        let mut data = DataCursor::new(args);
+        let fields = self.decode_named(&instruction.args, &mut data, 0)?;
+        data.finish()?;
+
+        let program_id = context.program_id();
+        let mut keys = context.instruction_accounts()?.into_iter();
+        let mut accounts = Vec::with_capacity(instruction.accounts.len());
+        for account in &instruction.accounts {
+            match keys.next() {
+                Some(key) if account.optional && key == program_id => {}
+                Some(key) => accounts.push(LabeledAccount::new(account.name.clone(), key)),
+                None if account.optional => {}
+                None => return Err(SoleanaError::NotEnoughAccounts),
+            }
+        }
+        accounts.extend(keys.map(|key| LabeledAccount::new("remaining", key)));
+
+        Ok(Box::new(DynamicInstruction {
+            program_name: self.idl.name.clone(),
+            instruction_name: instruction.name.clone(),
+            accounts,
+            fields,
+            data: context.data().to_vec(),
+        }))
+    }
+}
+
+/// An instruction parsed at runtime by an [`IdlParser`].
+///
+/// Two instructions are equal if their names, accounts and data are equal, the fields are
+/// decoded from the data.
+#[derive(Debug, Clone)]
+pub struct DynamicInstruction {
+    pub program_name: String,
+    pub instruction_name: String,
+    /// The accounts of the instruction labeled with their IDL names, the accounts past the
+    /// ones of the IDL are labeled `remaining`.
+    pub accounts: Vec<LabeledAccount>,
+    /// The arguments of the instruction, as a [`Value::Struct`].
+    pub fields: Value,
+    /// The data of the instruction, discriminator included.
+    pub data: Vec<u8>,
+}
+
+impl PartialEq for DynamicInstruction {
+    fn eq(&self, other: &Self) -> bool {
+        self.program_name == other.program_name
+            && self.instruction_name == other.instruction_name
+            && self.accounts == other.accounts
+            && self.data == other.data
+    }
+}
+
+impl Eq for DynamicInstruction {}
+
+impl ProgramInstructions for DynamicInstruction {
+    fn program_name(&self) -> &str {
+        &self.program_name
+    }
+
+    fn instruction_name(&self) -> &str {
+        &self.instruction_name
+    }
+
+    fn accounts(&self) -> Vec<LabeledAccount> {
+        self.accounts.clone()
+    }
+
+    fn fields(&self) -> Value {
+        self.fields.clone()
+    }
+}
+
+/// An event decoded at runtime by an [`IdlParser`].
+///
+/// Two events are equal if their names and data are equal, the fields are decoded from the
+/// data.
+#[derive(Debug, Clone)]
+pub struct DynamicEvent {
+    pub event_name: String,
+    /// The fields of the event, as a [`Value::Struct`].
+    pub fields: Value,
+    /// The data of the event, discriminator included.
+    pub data: Vec<u8>,
+}
+
+impl PartialEq for DynamicEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.event_name == other.event_name && self.data == other.data
+    }
+}
+
+impl Eq for DynamicEvent {}
+
+impl ProgramEvent for DynamicEvent {
+    fn event_name(&self) -> &str {
+        &self.event_name
+    }
+
+    fn fields(&self) -> Value {
+        self.fields.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{anchor::event_discriminator, registry::Registry, TransactionsParser};
+
+    const VAULT_IDL: &str = include_str!("../../fixtures/idl/vault.json");
+
+    /// Builds a transaction calling the vault with `instructions`, the accounts are `[0x0a; 32]`
+    /// (signer), `[0x0b; 32]`, `[0x0d; 32]` and the vault program `[0x0c; 32]`.
+    pub(crate) fn transaction(instructions: &[String]) -> String {
+        format!(
+            "01{}{}{}{}{:02x}{}",
+            "00".repeat(64),
+            "01000104",
+            "0a".repeat(32) + &"0b".repeat(32) + &"0d".repeat(32) + &"0c".repeat(32),
+            "00".repeat(32),
+            instructions.len(),
+            instructions.concat(),
+        )
+    }
+
+    /// Builds an instruction of the vault program from the hex of its accounts and data.
+    pub(crate) fn instruction(accounts: &str, data: &str) -> String {
+        format!(
+            "03{:02x}{accounts}{:02x}{data}",
+            accounts.len() / 2,
+            data.len() / 2
+        )
+    }
+
+    #[test]
+    fn test_load_anchor_idl() {
+        let idl = Idl::from_json(VAULT_IDL).unwrap();
+
+        assert_eq!(idl.address, Some([0x0c; 32]));
+        assert_eq!(idl.name, "vault");
+        assert_eq!(
+            idl.instructions[0]
+                .accounts
+                .iter()
+                .map(|account| account.name.as_str())
+                .collect::<Vec<_>>(),
+            ["user", "vault", "vault_authority", "referrer"]
+        );
+        assert!(idl.instructions[0].accounts[3].optional);
+        assert_eq!(
+            idl.instructions[1].args[2].ty,
+            IdlType::Array(Box::new(IdlType::U8), 4)
+        );
+
+        assert_eq!(
+            anchor::from_json(r#"{"metadata": {"name": "vault"}}"#),
+            Err(SoleanaError::InvalidIdl("missing `address`".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_with_idl() {
+        let parser = TransactionsParser::with_registry(Registry::builder().build());
+        assert_eq!(parser.register_idl(VAULT_IDL), Ok([0x0c; 32]));
+
+        let deposit = instruction(
+            "00010203",
+            &("f223c68952e1f2b6".to_owned()
+                + "e803000000000000"
+                + "01020000006869"
+                + "010500000000000000"
+                + "1e0001000000"
+                + &"0d".repeat(32)),
+        );
+        let withdraw = instruction(
+            "0001020301",
+            &("b712469c946da122".to_owned()
+                + "02000000"
+                + "0100000000000000"
+                + "0200000000000000"
+                + &"0b".repeat(32)
+                + "01020304"),
+        );
+
+        let transaction = parser
+            .parse_transaction(&transaction(&[deposit, withdraw]))
+            .unwrap();
+        let instructions = &transaction.message.instructions;
+
+        let deposit = instructions[0].parsed_as::<DynamicInstruction>().unwrap();
+        assert_eq!(deposit.program_name, "vault");
+        assert_eq!(deposit.instruction_name, "deposit");
+        assert_eq!(
+            deposit.accounts,
+            [
+                LabeledAccount::new("user", [0x0a; 32]),
+                LabeledAccount::new("vault", [0x0b; 32]),
+                LabeledAccount::new("vault_authority", [0x0d; 32]),
+            ]
+        );
+        assert_eq!(
+            deposit.fields,
+            Value::Struct(vec![
+                ("amount".to_string(), Value::U64(1000)),
+                (
+                    "memo".to_string(),
+                    Value::Option(Some(Box::new(Value::String("hi".to_string()))))
+                ),
+                (
+                    "side".to_string(),
+                    Value::Enum(
+                        "Ask".to_string(),
+                        Box::new(Value::Struct(vec![("limit".to_string(), Value::U64(5))]))
+                    )
+                ),
+                (
+                    "config".to_string(),
+                    Value::Struct(vec![
+                        ("fee_bps".to_string(), Value::U16(30)),
+                        (
+                            "owner".to_string(),
+                            Value::Option(Some(Box::new(Value::Pubkey([0x0d; 32]))))
+                        ),
+                    ])
+                ),
+            ])
+        );
+        assert_eq!(instructions[0].name.as_deref(), Some("deposit"));
+
+        let withdraw = instructions[1].parsed_as::<DynamicInstruction>().unwrap();
+        assert_eq!(withdraw.instruction_name, "withdraw");
+        assert_eq!(
+            withdraw.accounts.last(),
+            Some(&LabeledAccount::new("remaining", [0x0b; 32]))
+        );
+        assert_eq!(
+            withdraw.fields.get("amounts"),
+            Some(&Value::Array(vec![Value::U64(1), Value::U64(2)]))
+        );
+        assert_eq!(
+            withdraw.fields.get("destination"),
+            Some(&Value::Pubkey([0x0b; 32]))
+        );
+        assert_eq!(
+            withdraw.fields.get("seed"),
+            Some(&Value::Array(vec![
+                Value::U8(1),
+                Value::U8(2),
+                Value::U8(3),
+                Value::U8(4)
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_idl_events() {
+        let parser = TransactionsParser::with_registry(Registry::builder().build());
+        parser.register_idl(VAULT_IDL).unwrap();
+
+        let deposited = [
+            &event_discriminator("Deposited")[..],
+            &[0x0a; 32],
+            &1000u64.to_le_bytes(),
+        ]
+        .concat();
+        let vault = crate::base58::encode(&[0x0c; 32]);
+        let logs = [
+            format!("Program {vault} invoke [1]"),
+            format!("Program data: {}", crate::base64::encode(&deposited)),
+            format!("Program {vault} success"),
+        ];
+
+        let events = parser.parse_log_events(&logs).unwrap();
+        assert_eq!(events[0].name.as_deref(), Some("Deposited"));
+        let event = events[0].parsed_as::<DynamicEvent>().unwrap();
+        assert_eq!(
+            event.fields,
+            Value::Struct(vec![
+                ("user".to_string(), Value::Pubkey([0x0a; 32])),
+                ("amount".to_string(), Value::U64(1000)),
+            ])
+        );
+
+        let cpi = [&crate::events::EVENT_IX_TAG[..], &deposited].concat();
+        let event = parser.parse_cpi_event([0x0c; 32], &cpi).unwrap();
+        assert_eq!(event.parsed, events[0].parsed);
+
+        // Events carry no trailing bytes, the event is kept undecoded.
+        let cpi = [&cpi[..], &[0]].concat();
+        assert!(parser
+            .parse_cpi_event([0x0c; 32], &cpi)
+            .is_some_and(|event| event.parsed.is_none()));
+    }
+
+    #[test]
+    fn test_stale_idl() {
+        let parser = TransactionsParser::with_registry(Registry::builder().build());
+        parser.register_idl(VAULT_IDL).unwrap();
+
+        // The program was upgraded with a trailing `u8` arg on withdraw the IDL doesn't know.
+        let withdraw = instruction(
+            "00010203",
+            &("b712469c946da122".to_owned()
+                + "01000000"
+                + "0100000000000000"
+                + &"0b".repeat(32)
+                + "01020304"
+                + "2a"),
+        );
+        let deposit = instruction(
+            "000102",
+            &("f223c68952e1f2b6".to_owned() + "e803000000000000" + "00" + "00" + "1e0000000000"),
+        );
+
+        let transaction = parser
+            .parse_transaction(&transaction(&[withdraw, deposit]))
+            .unwrap();
+        let [withdraw, deposit] = &transaction.message.instructions[..] else {
+            panic!("expected two instructions");
+        };
+        assert!(withdraw.parsed.is_none());
+        assert_eq!(
+            deposit
+                .parsed_as::<DynamicInstruction>()
+                .map(|deposit| deposit.fields.get("amount")),
+            Some(Some(&Value::U64(1000)))
+        );
+    }
+
+    #[test]
+    fn test_idl_parse_errors() {
+        let parser = TransactionsParser::with_registry(Registry::builder().build());
+        parser.register_idl(VAULT_IDL).unwrap();
+
+        let missing_accounts = instruction("00", "b712469c946da1220000000000");
+        let truncated_args = instruction("00010203", "f223c68952e1f2b6e803");
+        let trailing_args = instruction("00010203", "f223c68952e1f2b6e80300000000000000");
+
+        // Instructions rejected by the IDL are left unparsed.
+        for instruction in [missing_accounts, truncated_args, trailing_args] {
+            let parsed = parser
+                .parse_transaction(&transaction(&[instruction]))
+                .unwrap();
+            assert!(parsed.message.instructions[0].parsed.is_none());
+        }
+
+        // An instruction missing from the IDL is left unparsed.
+        let unknown_discriminator = instruction("00", "0102030405060708");
+        let parsed = parser
+            .parse_transaction(&transaction(&[unknown_discriminator]))
+            .unwrap();
+        assert!(parsed.message.instructions[0].parsed.is_none());
+        assert_eq!(parsed.message.instructions[0].name, None);
+    }
+}
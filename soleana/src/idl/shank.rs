@@ -0,0 +1,132 @@
+use crate::{error::SoleanaResult, json::Json};
+
+use super::{
+    anchor::{array_field, field, invalid, str_field},
+    legacy, Idl, IdlDiscriminator,
+};
+
+/// Loads an IDL generated by Shank for a native program.
+///
+/// Shank IDLs share the layout of the legacy Anchor ones, but identify their instructions by the
+/// `discriminant` leading their data instead of the Anchor discriminator, and the program
+/// doesn't raise the framework errors of Anchor.
+pub fn from_json(json: &str) -> SoleanaResult<Idl> {
+    load(&Json::parse(json)?)
+}
+
+pub(crate) fn load(json: &Json) -> SoleanaResult<Idl> {
+    let mut idl = legacy::load(json)?;
+    idl.anchor = false;
+
+    for (instruction, json) in idl
+        .instructions
+        .iter_mut()
+        .zip(array_field(json, "instructions")?)
+    {
+        let bytes = discriminant(field(json, "discriminant")?)?;
+        instruction.args_offset = bytes.len();
+        instruction.discriminators = vec![IdlDiscriminator::Bytes { offset: 0, bytes }];
+    }
+
+    Ok(idl)
+}
+
+/// Returns whether `json` is an IDL generated by Shank.
+pub(crate) fn is_shank(json: &Json) -> bool {
+    json.get("metadata")
+        .and_then(|metadata| metadata.get("origin"))
+        .and_then(Json::as_str)
+        == Some("shank")
+}
+
+/// Encodes a `discriminant`, an unsigned integer in little-endian.
+fn discriminant(json: &Json) -> SoleanaResult<Vec<u8>> {
+    let size = match str_field(json, "type")? {
+        "u8" => 1,
+        "u16" => 2,
+        "u32" => 4,
+        "u64" => 8,
+        ty => return Err(invalid(format!("unsupported discriminant type `{ty}`"))),
+    };
+    let value = field(json, "value")?
+        .as_u64()
+        .filter(|&value| size == 8 || value < 1 << (size * 8))
+        .ok_or_else(|| invalid("`discriminant` doesn't fit its type"))?;
+
+    Ok(value.to_le_bytes()[..size].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        idl::{
+            tests::{instruction, transaction},
+            DynamicInstruction,
+        },
+        registry::Registry,
+        value::Value,
+        TransactionsParser,
+    };
+
+    const COUNTER_IDL: &str = include_str!("../../fixtures/idl/counter_shank.json");
+
+    #[test]
+    fn test_load_shank_idl() {
+        let idl = Idl::from_json(COUNTER_IDL).unwrap();
+        assert_eq!(idl.address, Some([0x0c; 32]));
+        assert!(!idl.anchor);
+        assert_eq!(idl.instructions[1].name, "increment");
+        assert_eq!(
+            idl.instructions[1].discriminators,
+            [IdlDiscriminator::Bytes {
+                offset: 0,
+                bytes: vec![1]
+            }]
+        );
+        assert_eq!(idl.instructions[1].args_offset, 1);
+        assert_eq!(idl.instructions[0].args[0].name, "initial_value");
+
+        let missing = COUNTER_IDL.replace(
+            r#""discriminant": { "type": "u8", "value": 1 }"#,
+            r#""docs": []"#,
+        );
+        assert_eq!(
+            Idl::from_json(&missing),
+            Err(invalid("missing `discriminant`"))
+        );
+
+        let discriminant = |json: &str| super::discriminant(&Json::parse(json).unwrap());
+        assert_eq!(
+            discriminant(r#"{"type": "u16", "value": 258}"#),
+            Ok(vec![2, 1])
+        );
+        assert!(discriminant(r#"{"type": "u8", "value": 256}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_shank_idl() {
+        let registry = Registry::builder().build();
+        let parser = TransactionsParser::with_registry(registry.clone());
+        assert_eq!(parser.register_idl(COUNTER_IDL), Ok([0x0c; 32]));
+
+        let increment = instruction("01", "0105000000");
+        let transaction = parser
+            .parse_transaction(&transaction(&[increment]))
+            .unwrap();
+        let increment = transaction.message.instructions[0]
+            .parsed_as::<DynamicInstruction>()
+            .unwrap();
+        assert_eq!(increment.instruction_name, "increment");
+        assert_eq!(increment.fields.get("by"), Some(&Value::U32(5)));
+
+        // A native program doesn't raise the framework errors of Anchor.
+        assert_eq!(
+            registry
+                .resolve_error(&[0x0c; 32], 0)
+                .map(|error| error.name),
+            Some("Overflow".to_string())
+        );
+        assert_eq!(registry.resolve_error(&[0x0c; 32], 2006), None);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::error::{SoleanaError, SoleanaResult};
+
+/// Maximum nesting of arrays and objects, deeper documents are rejected instead of overflowing
+/// the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A parsed JSON document.
+///
+/// Numbers keep their text so 64-bit integers (lamports, slots) don't lose precision, objects
+/// keep the order of their keys.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a JSON document, rejecting anything but whitespace after it.
+    pub(crate) fn parse(input: &str) -> SoleanaResult<Json> {
+        let mut parser = Parser {
+            bytes: input.as_bytes(),
+            cursor: 0,
+        };
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+
+        if parser.cursor != parser.bytes.len() {
+            return Err(SoleanaError::InvalidJson);
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the value of `key` if this is an object holding it.
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(field, _)| field == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Number(value) => value.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Number(value) => value.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_null(&self) -> bool {
+        matches!(self, Json::Null)
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.cursor += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.cursor).copied()
+    }
+
+    fn next(&mut self) -> SoleanaResult<u8> {
+        let byte = self.peek().ok_or(SoleanaError::InvalidJson)?;
+        self.cursor += 1;
+        Ok(byte)
+    }
+
+    fn expect(&mut self, literal: &[u8]) -> SoleanaResult<()> {
+        if !self.bytes[self.cursor..].starts_with(literal) {
+            return Err(SoleanaError::InvalidJson);
+        }
+
+        self.cursor += literal.len();
+        Ok(())
+    }
+
+    fn parse_value(&mut self, depth: usize) -> SoleanaResult<Json> {
+        if depth > MAX_DEPTH {
+            return Err(SoleanaError::InvalidJson);
+        }
+
+        self.skip_whitespace();
+        match self.peek().ok_or(SoleanaError::InvalidJson)? {
+            b'n' => self.expect(b"null").map(|_| Json::Null),
+            b't' => self.expect(b"true").map(|_| Json::Bool(true)),
+            b'f' => self.expect(b"false").map(|_| Json::Bool(false)),
+            b'"' => self.parse_string().map(Json::String),
+            b'[' => self.parse_array(depth),
+            b'{' => self.parse_object(depth),
+            b'-' | b'0'..=b'9' => self.parse_number(),
+            _ => Err(SoleanaError::InvalidJson),
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> SoleanaResult<Json> {
+        self.expect(b"[")?;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.cursor += 1;
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.parse_value(depth + 1)?);
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => continue,
+                b']' => return Ok(Json::Array(values)),
+                _ => return Err(SoleanaError::InvalidJson),
+            }
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> SoleanaResult<Json> {
+        self.expect(b"{")?;
+        let mut fields = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.cursor += 1;
+            return Ok(Json::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(b":")?;
+            fields.push((key, self.parse_value(depth + 1)?));
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => continue,
+                b'}' => return Ok(Json::Object(fields)),
+                _ => return Err(SoleanaError::InvalidJson),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> SoleanaResult<Json> {
+        let start = self.cursor;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+            self.cursor += 1;
+        }
+
+        let text = std::str::from_utf8(&self.bytes[start..self.cursor])
+            .map_err(|_| SoleanaError::InvalidJson)?;
+        if text.parse::<f64>().is_err() {
+            return Err(SoleanaError::InvalidJson);
+        }
+
+        Ok(Json::Number(text.to_string()))
+    }
+
+    fn parse_hex4(&mut self) -> SoleanaResult<u32> {
+        let digits = self
+            .bytes
+            .get(self.cursor..self.cursor + 4)
+            .ok_or(SoleanaError::InvalidJson)?;
+        let digits = std::str::from_utf8(digits).map_err(|_| SoleanaError::InvalidJson)?;
+        let value = u32::from_str_radix(digits, 16).map_err(|_| SoleanaError::InvalidJson)?;
+        self.cursor += 4;
+        Ok(value)
+    }
+
+    fn parse_string(&mut self) -> SoleanaResult<String> {
+        self.expect(b"\"")?;
+        let mut bytes = Vec::new();
+
+        loop {
+            match self.next()? {
+                b'"' => break,
+                b'\\' => match self.next()? {
+                    b'"' => bytes.push(b'"'),
+                    b'\\' => bytes.push(b'\\'),
+                    b'/' => bytes.push(b'/'),
+                    b'b' => bytes.push(0x08),
+                    b'f' => bytes.push(0x0c),
+                    b'n' => bytes.push(b'\n'),
+                    b'r' => bytes.push(b'\r'),
+                    b't' => bytes.push(b'\t'),
+                    b'u' => {
+                        let mut code = self.parse_hex4()?;
+                        // A high surrogate is followed by the low surrogate of the pair.
+                        if (0xd800..0xdc00).contains(&code) {
+                            self.expect(b"\\u")?;
+                            let low = self.parse_hex4()?;
+                            if !(0xdc00..0xe000).contains(&low) {
+                                return Err(SoleanaError::InvalidJson);
+                            }
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                        }
+
+                        let c = char::from_u32(code).ok_or(SoleanaError::InvalidJson)?;
+                        bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    }
+                    _ => return Err(SoleanaError::InvalidJson),
+                },
+                byte if byte < 0x20 => return Err(SoleanaError::InvalidJson),
+                byte => bytes.push(byte),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| SoleanaError::InvalidJson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let json = Json::parse(
+            r#" {"slot": 18446744073709551615, "err": null, "ok": [true, false, -1.5e3],
+                "name": "a\"\u00e9\ud83d\ude00", "nested": {"empty": [], "object": {}}} "#,
+        )
+        .unwrap();
+
+        assert_eq!(json.get("slot").unwrap().as_u64(), Some(u64::MAX));
+        assert_eq!(json.get("err"), Some(&Json::Null));
+        assert_eq!(
+            json.get("ok").unwrap().as_array().unwrap()[0].as_bool(),
+            Some(true)
+        );
+        assert_eq!(json.get("name").unwrap().as_str(), Some("a\"é😀"));
+        assert_eq!(
+            json.get("nested").unwrap().get("empty").unwrap().as_array(),
+            Some(&[][..])
+        );
+        assert!(json.get("missing").is_none());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_documents() {
+        for invalid in ["", "{", "[1,]", "{\"a\" 1}", "tru", "\"\\x\"", "1 2", "-"] {
+            assert_eq!(
+                Json::parse(invalid),
+                Err(SoleanaError::InvalidJson),
+                "{invalid}"
+            );
+        }
+
+        assert_eq!(
+            Json::parse(&"[".repeat(10_000)),
+            Err(SoleanaError::InvalidJson)
+        );
+    }
+}
@@ -1,17 +1,58 @@
+extern crate self as soleana;
+
+#[cfg(feature = "derive")]
+pub use soleana_derive::{Accounts, Program};
+
 /// Reader module implements the logic to read a buffer of bytes.
 pub mod reader;
 
+/// Shortvec module implements the compact u16 encoding used by the transaction format.
+pub mod shortvec;
+
+/// Sha256 module implements a dependency-free sha256.
+pub mod sha256;
+
+/// Anchor module implements the Anchor discriminators.
+pub mod anchor;
+
+/// Base58 module implements the encoding of Solana addresses and signatures.
+pub mod base58;
+
+/// Base64 module implements the encoding of the RPC transactions and the program data logs.
+pub mod base64;
+
+/// Json module implements the JSON parser used to load IDLs.
+mod json;
+
+/// Idl module implements the runtime parsers built from program IDLs.
+pub mod idl;
+
 /// Error module implements the error types for the library.
 pub mod error;
 
 /// Types module implements the types for the library.
 pub mod types;
 
+/// Value module implements the dynamic representation of parsed instructions.
+pub mod value;
+
+/// Limits module implements the resource limits enforced while parsing.
+pub mod limits;
+
 /// Programs module implements the logic to parse various programs.
 pub mod programs;
 
+/// Events module implements the decoding of the Anchor events of logs and self-CPI instructions.
+pub mod events;
+
+/// Program errors module implements the tables of the custom error codes of the programs.
+pub mod program_errors;
+
+/// Rpc module implements the parsing of the JSON-RPC responses of the transactions.
+pub mod rpc;
+
 /// Registry module implements the logic to register programs.
-pub(crate) mod registry;
+pub mod registry;
 
 /// Prelude module implements the prelude for the library.
 pub mod prelude;
@@ -19,42 +60,186 @@ pub mod prelude;
 /// TransactionsParser module implements the logic to parse transactions.
 use crate::{
     error::SoleanaResult,
-    programs::{compute_budget::ComputeBudget, system::System, Program, ProgramInstructions},
+    limits::ParserLimits,
+    programs::context::ParseContext,
+    programs::{InstructionParser, Program, ProgramInstructions},
     reader::Reader,
-    types::{Indicator, Instruction, Pubkey},
+    registry::{Registry, RegistryInner},
+    types::{Instruction, Pubkey},
 };
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 /// [`TransactionsParser`] is a struct that uses a [`Reader`] to parse transactions.
 ///
 /// This struct is used so we can pass various `Program`'s to the parser only once, and then parse various transactions.
-pub struct TransactionsParser<'a> {
-    pub(crate) reader: Reader<'a>,
+///
+/// The parser is `Send + Sync` and every parse creates its own [`Reader`], so a single parser
+/// can be shared between threads.
+pub struct TransactionsParser {
+    pub(crate) registry: Arc<Registry>,
+    pub(crate) limits: ParserLimits,
 }
 
-impl<'a> TransactionsParser<'a> {
-    /// Creates a new [`TransactionsParser`] from a buffer of bytes.
+impl TransactionsParser {
+    /// Creates a new [`TransactionsParser`] using the global default [`Registry`].
+    ///
+    /// Programs and luts registered through this parser are shared with every other parser
+    /// using the global registry, use [`TransactionsParser::with_registry`] to keep them apart.
     pub fn new() -> Self {
-        registry::register_program::<System>();
-        registry::register_program::<ComputeBudget>();
+        Self::with_registry(Registry::global())
+    }
+
+    /// Creates a new [`TransactionsParser`] with its own empty [`Registry`], without the built-in programs.
+    pub fn without_builtins() -> Self {
+        Self::with_registry(Registry::builder().without_builtins().build())
+    }
 
+    /// Creates a new [`TransactionsParser`] using the given [`Registry`].
+    pub fn with_registry(registry: Arc<Registry>) -> Self {
         Self {
-            reader: Reader::new_empty(),
+            registry,
+            limits: ParserLimits::default(),
         }
     }
 
+    /// Returns the [`Registry`] used by the parser.
+    pub fn registry(&self) -> &Arc<Registry> {
+        &self.registry
+    }
+
+    /// Sets the [`ParserLimits`] enforced while parsing transactions.
+    pub fn with_limits(mut self, limits: ParserLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Registers a program to the parser.
-    pub fn register_program<P: Program>(&self)
+    pub fn register_program<P: Program + 'static>(&self)
+    where
+        P::Instructions: ProgramInstructions + 'static,
+    {
+        self.registry.register_program::<P>();
+    }
+
+    /// Registers a program to the parser under every id of `ids`, instead of its own ids.
+    pub fn register_program_with_ids<P: Program + 'static>(&self, ids: &[Pubkey])
+    where
+        P::Instructions: ProgramInstructions + 'static,
+    {
+        self.registry.register_program_with_ids::<P>(ids);
+    }
+
+    /// Replaces the parsers registered under the ids of `P` with `P`.
+    pub fn replace_program<P: Program + 'static>(&self) -> bool
     where
         P::Instructions: ProgramInstructions + 'static,
     {
-        registry::register_program::<P>();
+        self.registry.replace_program::<P>()
+    }
+
+    /// Removes the parser registered under `program_id`.
+    pub fn unregister_program(&self, program_id: &Pubkey) -> bool {
+        self.registry.unregister_program(program_id)
+    }
+
+    /// Returns the id and the name, if known, of every registered program, sorted by id.
+    pub fn registered_programs(&self) -> Vec<(Pubkey, Option<String>)> {
+        self.registry.registered_programs()
+    }
+
+    /// Registers a stateful parser to the parser under `program_id`.
+    pub fn register_parser(&self, program_id: Pubkey, parser: Arc<dyn InstructionParser>) {
+        self.registry.register_parser(program_id, parser);
+    }
+
+    /// Registers a closure as the parser of `program_id`.
+    pub fn register_parser_fn<F>(&self, program_id: Pubkey, parser: F)
+    where
+        F: Fn(Pubkey, &Vec<u8>, &[u8], &[Pubkey]) -> SoleanaResult<Box<dyn ProgramInstructions>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.registry.register_parser_fn(program_id, parser);
+    }
+
+    /// Loads an IDL from its JSON and registers its parser under the IDL address.
+    ///
+    /// Returns the address the parser was registered under.
+    pub fn register_idl(&self, json: &str) -> SoleanaResult<Pubkey> {
+        self.registry.register_idl(idl::Idl::from_json(json)?)
+    }
+
+    /// Loads an IDL from its JSON and registers its parser under `program_id`.
+    ///
+    /// Needed for legacy Anchor IDLs, which don't carry the address of their program.
+    pub fn register_idl_with_id(&self, json: &str, program_id: Pubkey) -> SoleanaResult<()> {
+        self.registry
+            .register_idl_with_id(idl::Idl::from_json(json)?, program_id);
+        Ok(())
+    }
+
+    /// Names the instructions of `program_id` whose data starts with `discriminator`.
+    pub fn register_instruction_name<S: Into<String>>(
+        &self,
+        program_id: Pubkey,
+        discriminator: anchor::Discriminator,
+        name: S,
+    ) {
+        self.registry
+            .register_instruction_name(program_id, discriminator, name);
+    }
+
+    /// Names the instructions of the Anchor program `program_id` from their handler names.
+    pub fn register_anchor_instructions(&self, program_id: Pubkey, names: &[&str]) {
+        self.registry
+            .register_anchor_instructions(program_id, names);
+    }
+
+    /// Registers the hand-written event `E` of `program_id`.
+    pub fn register_event<E: events::Event + 'static>(&self, program_id: Pubkey) {
+        self.registry.register_event::<E>(program_id);
+    }
+
+    /// Extracts the events of the `Program data:` lines of `logs`, each one attributed to the
+    /// program executing when it was logged.
+    pub fn parse_log_events<S: AsRef<str>>(
+        &self,
+        logs: &[S],
+    ) -> SoleanaResult<Vec<events::EmittedEvent>> {
+        self.registry.log_events(logs)
+    }
+
+    /// Extracts the event of an `emit_cpi!` instruction of `program_id`, `None` if the
+    /// instruction isn't one.
+    pub fn parse_cpi_event(&self, program_id: Pubkey, data: &[u8]) -> Option<events::EmittedEvent> {
+        self.registry.cpi_event(program_id, data)
+    }
+
+    /// Registers the custom error `error` of `program_id`.
+    pub fn register_error(&self, program_id: Pubkey, error: program_errors::CustomError) {
+        self.registry.register_error(program_id, error);
+    }
+
+    /// Marks `program_id` as built with Anchor, so its errors include the framework codes.
+    pub fn register_anchor_program(&self, program_id: Pubkey) {
+        self.registry.register_anchor_program(program_id);
+    }
+
+    /// Resolves the custom error `code` raised by `program_id`, the `n` of
+    /// `InstructionError::Custom(n)`.
+    pub fn resolve_error(
+        &self,
+        program_id: &Pubkey,
+        code: u32,
+    ) -> Option<program_errors::CustomError> {
+        self.registry.resolve_error(program_id, code)
     }
 
     /// Registers a lut to the parser.
     pub fn register_lut<T: Into<crate::types::CompleteAddressLookupTable>>(&self, lut: T) {
-        registry::register_lut(lut.into());
+        self.registry.register_lut(lut);
     }
 
     /// Registers a lut fetch function to the parser.
@@ -63,83 +248,152 @@ impl<'a> TransactionsParser<'a> {
         F: Fn(&[u8; 32]) -> R + Send + Sync + 'static,
         R: Into<crate::types::CompleteAddressLookupTable>,
     {
-        let mut registry = registry::registry().write().unwrap();
-        registry.lut_fetch_fn = Some(Box::new(move |key| fetch_fn(key).into()));
+        self.registry.register_lut_fetch_fn(fetch_fn);
     }
 
     /// Fetches a lut from the fetch function and registers it to the parser.
     pub fn fetch_and_register_lut(&self, lut_account: Pubkey) -> SoleanaResult<()> {
-        let registry = registry::registry().read().unwrap();
+        self.registry.fetch_and_register_lut(lut_account)
+    }
 
-        let fetch_fn = registry
-            .lut_fetch_fn
-            .as_ref()
-            .ok_or_else(|| crate::error::SoleanaError::NoLutFetchFnRegistered)?;
+    /// Parses an instruction with its registered program.
+    ///
+    /// Instructions of unknown programs, and the ones their parser rejects, are left unparsed
+    /// whatever their stack height, so a single instruction never fails the transaction.
+    /// Unparsed instructions are still named if their discriminator is registered.
+    fn parse_instruction(context: &ParseContext, registry: &RegistryInner) -> Instruction {
+        let parsed = registry
+            .programs
+            .get(&context.program_id())
+            .and_then(|parser| parser.parse_with_context(context).ok());
 
-        self.register_lut((fetch_fn)(&lut_account));
+        let name = parsed
+            .as_ref()
+            .map(|parsed| parsed.instruction_name())
+            .filter(|&name| name != programs::UNKNOWN_NAME)
+            .or_else(|| registry.instruction_name(&context.program_id(), context.data()));
 
-        Ok(())
+        Instruction {
+            program_id: context.program_id(),
+            account_indexes: context.instruction.account_indexes.clone(),
+            name: name.map(str::to_string),
+            parsed,
+            raw: context.instruction.data.clone(),
+            stack_height: context.stack_height,
+            inner_instructions: Vec::new(),
+        }
     }
 
-    fn parse_instructions(
+    /// Reads a message from the reader, starting at the version prefix.
+    ///
+    /// The accounts of the luts are resolved from `loaded` when given, from the registered luts
+    /// otherwise.
+    fn read_message(
         &self,
-        instructions: Vec<(Pubkey, Vec<u8>, Vec<u8>)>,
-        accounts: &[Pubkey],
-        programs: &HashMap<Pubkey, crate::registry::ParserFn>,
-    ) -> SoleanaResult<Vec<Instruction>> {
-        instructions
-            .iter()
-            .map(|(program_id, ix_acc, data)| {
-                let parsed: Option<Box<dyn ProgramInstructions + 'static>> =
-                    if let Some(parser) = programs.get(program_id) {
-                        let instruction = parser(*program_id, ix_acc, data, accounts)?;
-                        Some(instruction)
-                    } else {
-                        None
-                    };
-                Ok(Instruction {
-                    program_id: *program_id,
-                    parsed,
-                    raw: data.clone(),
-                })
-            })
-            .collect()
-    }
+        reader: &mut Reader,
+        loaded: Option<&rpc::LoadedAddresses>,
+    ) -> SoleanaResult<types::Message> {
+        let indicator = reader.indicator()?;
+        let header = reader.read_header()?;
+        let mut accounts = reader.read_accounts()?;
+        let account_keys = accounts.clone();
+        let hash = reader.read_hash()?;
+        let instructions = reader.read_instructions(&accounts)?;
 
-    /// Parses a transaction from a hex string.
-    ///
-    /// TODO: This should either accept a &'a str or a &'a [u8]
-    pub fn parse_transaction(&mut self, transaction: &'a str) -> SoleanaResult<types::Transaction> {
-        self.reader.set_bytes_from_str(transaction)?;
-
-        let signatures = self.reader.read_signatures()?;
-        let indicator = self.reader.indicator()?;
-        let header = self.reader.read_header()?;
-        let mut accounts = self.reader.read_accounts()?;
-        let hash = self.reader.read_hash()?;
-        let instructions = self.reader.read_instructions(&accounts)?;
-
-        let luts: Option<Vec<crate::types::LUT>> = match indicator {
-            Indicator::Legacy => None,
-            Indicator::V0 => Some(self.reader.read_luts(&mut accounts)?),
+        let registry = self.registry.read();
+        let luts = match loaded {
+            Some(loaded) => {
+                let luts =
+                    reader.read_versioned_sections(indicator, &mut accounts, &HashMap::new())?;
+                loaded.resolve(luts.as_deref(), &mut accounts)?;
+                luts
+            }
+            None => reader.read_versioned_sections(indicator, &mut accounts, &registry.luts)?,
         };
 
-        let instructions = self.parse_instructions(
-            instructions,
-            &accounts,
-            &registry::registry().read().unwrap().programs,
-        )?;
+        let instructions = instructions
+            .iter()
+            .enumerate()
+            .map(|(instruction_index, instruction)| {
+                let context = ParseContext {
+                    instruction,
+                    instruction_index,
+                    stack_height: Some(1),
+                    instructions: &instructions,
+                    accounts: &accounts,
+                    static_accounts_len: account_keys.len(),
+                    luts: luts.as_deref(),
+                    header: &header,
+                    version: indicator,
+                };
+                Self::parse_instruction(&context, &registry)
+            })
+            .collect();
 
-        let transaction = types::Transaction {
+        Ok(types::Message {
             transaction_type: indicator,
-            signatures,
             header,
+            account_keys,
             hash,
             instructions,
             luts,
-        };
+        })
+    }
 
-        Ok(transaction)
+    /// Reads a transaction from the reader, starting at the signatures.
+    fn read_transaction(
+        &self,
+        reader: &mut Reader,
+        loaded: Option<&rpc::LoadedAddresses>,
+    ) -> SoleanaResult<types::Transaction> {
+        let signatures = reader.read_signatures()?;
+        let message = self.read_message(reader, loaded)?;
+
+        Ok(types::Transaction {
+            signatures,
+            message,
+        })
+    }
+
+    /// Parses a transaction from a hex string.
+    pub fn parse_transaction(&self, transaction: &str) -> SoleanaResult<types::Transaction> {
+        let mut reader = Reader::new_empty().with_limits(self.limits);
+        reader.set_bytes_from_str(transaction)?;
+        self.read_transaction(&mut reader, None)
+    }
+
+    /// Parses a transaction from its serialized bytes.
+    pub fn parse_transaction_bytes(&self, transaction: &[u8]) -> SoleanaResult<types::Transaction> {
+        let mut reader = Reader::new_empty().with_limits(self.limits);
+        reader.set_bytes(transaction.into())?;
+        self.read_transaction(&mut reader, None)
+    }
+
+    /// Parses a serialized message, a transaction without the signatures section.
+    ///
+    /// This is the format handed out by wallet signing requests and `simulateTransaction` tooling.
+    pub fn parse_message(&self, message: &[u8]) -> SoleanaResult<types::Message> {
+        let mut reader = Reader::new_empty().with_limits(self.limits);
+        reader.set_bytes(message.into())?;
+        self.read_message(&mut reader, None)
+    }
+
+    /// Parses a `getTransaction` JSON-RPC response, the envelope or its `result`.
+    ///
+    /// The transaction must be encoded in base64 or base58, the accounts of its luts are
+    /// resolved from `meta.loadedAddresses`, or from the registered luts when the node didn't
+    /// record them.
+    ///
+    /// The inner instructions of the meta are parsed with the registered programs too, and
+    /// attached to the top-level instruction that invoked them.
+    pub fn parse_rpc_transaction(&self, json: &str) -> SoleanaResult<rpc::RpcTransaction> {
+        rpc::parse_rpc_transaction(self, json)
+    }
+}
+
+impl Default for TransactionsParser {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -147,10 +401,291 @@ impl<'a> TransactionsParser<'a> {
 mod tests {
     use super::*;
 
+    /// A v0 transaction setting a compute unit limit of 1000 and transferring 8000 lamports.
+    const V0_TRANSFER: &str = "01c79cc65469fdfcc8fb10150150e33c73220b976162999d1e38a81176de3aaf90af7f39eacbd261932badd65c3551cdac3f1e60585e2c92e3b52f117bac35750680010002040e7698886e86cd5f4faf3ab562b70f97736ffd2c62eaa7bfe194a2021a82d97cbf971b59108b5b85a04fb093f1e21b4e3fd4c4c8f487dd09b95752769f0dd8c300000000000000000000000000000000000000000000000000000000000000000306466fe5211732ffecadba72c39be7bc8ce5bbc5f7126b2c439b3a400000000124ad783cd3b62be732496acc325d8337e80f1fa06d278a9b534f28fe60a4740203000502e8030000020200010c02000000401f00000000000000";
+
     #[test]
     fn test_parse_transaction() {
-        let mut parser = TransactionsParser::new();
-        let transaction = parser.parse_transaction("01c79cc65469fdfcc8fb10150150e33c73220b976162999d1e38a81176de3aaf90af7f39eacbd261932badd65c3551cdac3f1e60585e2c92e3b52f117bac35750680010002040e7698886e86cd5f4faf3ab562b70f97736ffd2c62eaa7bfe194a2021a82d97cbf971b59108b5b85a04fb093f1e21b4e3fd4c4c8f487dd09b95752769f0dd8c300000000000000000000000000000000000000000000000000000000000000000306466fe5211732ffecadba72c39be7bc8ce5bbc5f7126b2c439b3a400000000124ad783cd3b62be732496acc325d8337e80f1fa06d278a9b534f28fe60a4740203000502e8030000020200010c02000000401f00000000000000");
-        println!("{:?}", transaction.unwrap());
+        use programs::{
+            compute_budget::ComputeBudgetInstructions,
+            system::{SystemInstructions, SystemTransferAccounts},
+        };
+
+        let parser = TransactionsParser::new();
+        let transaction = parser.parse_transaction(V0_TRANSFER).unwrap();
+        assert_eq!(transaction.signatures.len(), 1);
+
+        let message = &transaction.message;
+        assert_eq!(message.transaction_type, types::Indicator::V0);
+        assert_eq!(message.account_keys.len(), 4);
+        assert_eq!(message.luts, Some(Vec::new()));
+
+        let [compute_budget, transfer] = &message.instructions[..] else {
+            panic!("expected two instructions");
+        };
+        assert_eq!(compute_budget.name.as_deref(), Some("SetComputeUnitLimit"));
+        assert_eq!(
+            compute_budget.parsed_as(),
+            Some(&ComputeBudgetInstructions::SetComputeUnitLimit { units: 1000 })
+        );
+        assert_eq!(transfer.name.as_deref(), Some("Transfer"));
+        assert_eq!(transfer.account_indexes, vec![0, 1]);
+        assert_eq!(
+            transfer.parsed_as(),
+            Some(&SystemInstructions::Transfer {
+                lamports: 8000,
+                accounts: SystemTransferAccounts {
+                    from: message.account_keys[0],
+                    to: message.account_keys[1],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_builtin_instructions() {
+        let compute_budget = "0306466fe5211732ffecadba72c39be7bc8ce5bbc5f7126b2c439b3a40000000";
+        let transaction = format!(
+            "02{}{}{}{}03{}{}{}",
+            "00".repeat(128),
+            // Header and the account keys: the payer, the new account and both programs.
+            "02000204",
+            "0a".repeat(32) + &"0b".repeat(32) + &"00".repeat(32) + compute_budget,
+            "ee".repeat(32),
+            // SetLoadedAccountsDataSizeLimit, CreateAccount and a transfer of 1000 lamports.
+            "0300050400000400",
+            "0202000134".to_owned()
+                + "00000000"
+                + "e803000000000000"
+                + "a500000000000000"
+                + &"0c".repeat(32),
+            "020200010c02000000e803000000000000",
+        );
+
+        let parser = TransactionsParser::new();
+        let transaction = parser.parse_transaction(&transaction).unwrap();
+        let [set_data_size_limit, create_account, transfer] = &transaction.message.instructions[..]
+        else {
+            panic!("expected three instructions");
+        };
+
+        // Variants the built-in parsers don't implement are left unparsed.
+        assert!(set_data_size_limit.parsed.is_none());
+        assert!(create_account.parsed.is_none());
+        assert_eq!(create_account.account_indexes, vec![0, 1]);
+        assert!(transfer.parsed.is_some());
+    }
+
+    #[test]
+    fn test_registries_are_isolated() {
+        struct Noop;
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct NoopInstructions;
+
+        impl ProgramInstructions for NoopInstructions {}
+
+        impl Program for Noop {
+            fn program_id() -> Pubkey {
+                [0x0c; 32]
+            }
+
+            type Instructions = NoopInstructions;
+
+            fn parse_instruction(
+                _: Pubkey,
+                _: &Vec<u8>,
+                _: &[u8],
+                _: &[Pubkey],
+            ) -> SoleanaResult<Self::Instructions> {
+                Ok(NoopInstructions)
+            }
+        }
+
+        let transaction = format!(
+            "01{}{}{}{}{}",
+            "11".repeat(64),
+            "01000102",
+            "0a".repeat(32) + &"0c".repeat(32),
+            "00".repeat(32),
+            "0101000100",
+        );
+
+        let with_noop =
+            TransactionsParser::with_registry(Registry::builder().program::<Noop>().build());
+        let without_noop = TransactionsParser::with_registry(Registry::builder().build());
+
+        let parsed = with_noop.parse_transaction(&transaction).unwrap();
+        assert!(parsed.message.instructions[0].parsed.is_some());
+
+        let parsed = without_noop.parse_transaction(&transaction).unwrap();
+        assert!(parsed.message.instructions[0].parsed.is_none());
+    }
+
+    #[test]
+    fn test_downcast_parsed_instructions() {
+        use programs::{
+            compute_budget::ComputeBudgetInstructions, system::SystemInstructions, Instructions,
+        };
+
+        let parser = TransactionsParser::new();
+        let transaction = parser.parse_transaction(V0_TRANSFER).unwrap();
+        let [compute_budget, transfer]: [Instruction; 2] =
+            transaction.message.instructions.try_into().unwrap();
+
+        assert!(matches!(
+            compute_budget.parsed_as::<ComputeBudgetInstructions>(),
+            Some(ComputeBudgetInstructions::SetComputeUnitLimit { units: 1000 })
+        ));
+        assert!(transfer.parsed_as::<ComputeBudgetInstructions>().is_none());
+
+        let parsed = transfer.parsed.as_deref().unwrap();
+        assert_eq!(parsed.program_name(), "System");
+        assert_eq!(parsed.instruction_name(), "Transfer");
+        assert_eq!(
+            parsed
+                .accounts()
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>(),
+            ["from", "to"]
+        );
+        assert_eq!(
+            parsed.fields().get("lamports"),
+            Some(&value::Value::U64(8000))
+        );
+
+        assert!(matches!(
+            transfer.into_instructions(),
+            Some(Instructions::System(SystemInstructions::Transfer {
+                lamports: 8000,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_clone_and_compare_transactions() {
+        let parser = TransactionsParser::new();
+        let transaction = parser.parse_transaction(V0_TRANSFER).unwrap();
+
+        let mut cloned = transaction.clone();
+        assert_eq!(cloned, transaction);
+        assert_eq!(
+            std::collections::HashSet::from([transaction.clone(), cloned.clone()]).len(),
+            1
+        );
+
+        // Same raw instruction, but parsed into a different value.
+        cloned.message.instructions[1].parsed = Some(Box::new(
+            programs::compute_budget::ComputeBudgetInstructions::SetComputeUnitLimit { units: 1 },
+        ));
+        assert_ne!(cloned, transaction);
+    }
+
+    #[test]
+    fn test_parser_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<TransactionsParser>();
+    }
+
+    #[test]
+    fn test_parse_message() {
+        let transaction = V0_TRANSFER;
+        // Skip the signatures section: one compact u16 byte and a 64-byte signature.
+        let message_bytes: Vec<u8> = (2 + 128..transaction.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&transaction[i..i + 2], 16).unwrap())
+            .collect();
+
+        let parser = TransactionsParser::new();
+        let message = parser.parse_message(&message_bytes).unwrap();
+        assert_eq!(message.transaction_type, types::Indicator::V0);
+        assert_eq!(message.account_keys.len(), 4);
+        assert_eq!(message.instructions.len(), 2);
+        assert!(message.instructions.iter().all(|ix| ix.parsed.is_some()));
+
+        let parser = TransactionsParser::new();
+        let transaction = parser.parse_transaction(transaction).unwrap();
+        assert_eq!(transaction.signatures.len(), 1);
+        assert_eq!(transaction.message.hash, message.hash);
+    }
+
+    #[test]
+    fn test_merge_signatures() {
+        // Two signers, the fee payer signed the first copy and the second signer the other one.
+        let message = format!(
+            "{}{}{}{}",
+            // Header and the account keys: two signers, a recipient and the system program.
+            "02000104",
+            "0a".repeat(32) + &"0b".repeat(32) + &"0c".repeat(32) + &"00".repeat(32),
+            "00".repeat(32),
+            // A single system transfer of 64 lamports from the fee payer.
+            "01030200020c020000004000000000000000",
+        );
+        let first = format!("02{}{}{message}", "11".repeat(64), "00".repeat(64));
+        let second = format!("02{}{}{message}", "00".repeat(64), "22".repeat(64));
+
+        let parser = TransactionsParser::new();
+        let mut first = parser.parse_transaction(&first).unwrap();
+        let parser = TransactionsParser::new();
+        let second = parser.parse_transaction(&second).unwrap();
+
+        assert_eq!(first.missing_signers(), vec![[0x0b; 32]]);
+        assert_eq!(second.missing_signers(), vec![[0x0a; 32]]);
+        assert!(!first.is_fully_signed());
+
+        first.merge_signatures(&second).unwrap();
+        assert!(first.is_fully_signed());
+        assert_eq!(
+            first.signers().collect::<Vec<_>>(),
+            vec![(&[0x0a; 32], &[0x11; 64]), (&[0x0b; 32], &[0x22; 64])]
+        );
+    }
+
+    #[test]
+    fn test_durable_nonce() {
+        let transaction = format!(
+            "01{}{}{}{}{}",
+            "11".repeat(64),
+            // Header and the account keys: authority, nonce account, sysvar and the system program.
+            "01010204",
+            "0a".repeat(32) + &"0b".repeat(32) + &"0c".repeat(32) + &"00".repeat(32),
+            "ee".repeat(32),
+            // A single AdvanceNonceAccount.
+            "0103030102000404000000",
+        );
+
+        let parser = TransactionsParser::new();
+        let transaction = parser.parse_transaction(&transaction).unwrap();
+        let nonce = types::DurableNonce {
+            nonce: [0xee; 32],
+            nonce_account: [0x0b; 32],
+            authority: [0x0a; 32],
+        };
+        assert_eq!(transaction.durable_nonce(), Some(nonce));
+        assert_eq!(
+            transaction.lifetime(),
+            types::TransactionLifetime::DurableNonce(nonce)
+        );
+        assert!(matches!(
+            transaction.message.instructions[0].parsed_as(),
+            Some(programs::system::SystemInstructions::AdvanceNonceAccount { .. })
+        ));
+        // A readonly nonce account can't be advanced, the hash is a recent blockhash.
+        let readonly = format!(
+            "01{}{}{}{}{}",
+            "11".repeat(64),
+            "01010304",
+            "0a".repeat(32) + &"0b".repeat(32) + &"0c".repeat(32) + &"00".repeat(32),
+            "ee".repeat(32),
+            "0103030102000404000000",
+        );
+        let transaction = parser.parse_transaction(&readonly).unwrap();
+        assert_eq!(transaction.durable_nonce(), None);
+        assert_eq!(
+            transaction.lifetime(),
+            types::TransactionLifetime::RecentBlockhash([0xee; 32])
+        );
     }
 }
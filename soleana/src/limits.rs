@@ -0,0 +1,61 @@
+/// Maximum size of a serialized transaction, as enforced by the validator. <https://docs.rs/solana-packet/latest/solana_packet/constant.PACKET_DATA_SIZE.html>
+pub const PACKET_DATA_SIZE: usize = 1232;
+
+/// Limits enforced by the [`Reader`](crate::reader::Reader) while decoding untrusted input.
+///
+/// Every length prefix read from the buffer is checked against these limits (and against the
+/// bytes that are actually left) before anything is allocated for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserLimits {
+    /// Maximum number of bytes of a single transaction.
+    pub max_input_bytes: usize,
+    /// Maximum number of signatures.
+    pub max_signatures: usize,
+    /// Maximum number of static account keys.
+    pub max_accounts: usize,
+    /// Maximum number of top-level instructions.
+    pub max_instructions: usize,
+    /// Maximum length of the data of a single instruction.
+    pub max_instruction_data_len: usize,
+    /// Maximum number of address lookup tables.
+    pub max_luts: usize,
+}
+
+impl ParserLimits {
+    /// Limits that never reject a transaction, only the size of the buffer is taken into account.
+    pub const fn unlimited() -> Self {
+        Self {
+            max_input_bytes: usize::MAX,
+            max_signatures: usize::MAX,
+            max_accounts: usize::MAX,
+            max_instructions: usize::MAX,
+            max_instruction_data_len: usize::MAX,
+            max_luts: usize::MAX,
+        }
+    }
+}
+
+impl Default for ParserLimits {
+    /// Limits derived from the protocol, any transaction accepted by the validator fits in them.
+    fn default() -> Self {
+        Self {
+            max_input_bytes: PACKET_DATA_SIZE,
+            max_signatures: u8::MAX as usize,
+            max_accounts: u8::MAX as usize + 1,
+            max_instructions: u8::MAX as usize,
+            max_instruction_data_len: PACKET_DATA_SIZE,
+            max_luts: u8::MAX as usize,
+        }
+    }
+}
+
+/// Identifies which of the [`ParserLimits`] was exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    InputBytes,
+    Signatures,
+    Accounts,
+    Instructions,
+    InstructionData,
+    Luts,
+}
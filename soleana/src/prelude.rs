@@ -1,7 +1,16 @@
 pub mod program_impl {
     pub use crate::{
         error::{SoleanaError, SoleanaResult},
-        programs::{Program, ProgramInstructions},
-        types::Pubkey,
+        events::{Event, ProgramEvent},
+        programs::{
+            context::{AccountMeta, Accounts, ParseContext},
+            decode::{DataCursor, Decode},
+            InstructionParser, Program, ProgramInstructions,
+        },
+        types::{Cluster, Pubkey},
+        value::{LabeledAccount, ToValue, Value},
     };
+
+    #[cfg(feature = "derive")]
+    pub use soleana_derive::{Accounts, Program};
 }
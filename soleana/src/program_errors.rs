@@ -0,0 +1,343 @@
+use crate::types::Pubkey;
+
+const SYSTEM_PROGRAM_ID: Pubkey = [0; 32];
+
+/// The id of the SPL Token program, `TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA`.
+pub const TOKEN_PROGRAM_ID: Pubkey = [
+    6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 133, 237,
+    95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169,
+];
+
+/// The id of the SPL Token-2022 program, `TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb`.
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = [
+    6, 221, 246, 225, 238, 117, 143, 222, 24, 66, 93, 188, 228, 108, 205, 218, 182, 26, 252, 77,
+    131, 185, 13, 39, 254, 189, 249, 40, 216, 161, 139, 252,
+];
+
+/// A custom error of a program, the `n` of `InstructionError::Custom(n)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CustomError {
+    pub code: u32,
+    pub name: String,
+    pub message: Option<String>,
+}
+
+impl CustomError {
+    pub fn new<N: Into<String>, M: Into<String>>(code: u32, name: N, message: M) -> Self {
+        Self {
+            code,
+            name: name.into(),
+            message: Some(message.into()),
+        }
+    }
+}
+
+/// A built-in table, `(code, name, message)` sorted by code.
+type Table = &'static [(u32, &'static str, &'static str)];
+
+/// The `SystemError` of the System program.
+#[rustfmt::skip]
+const SYSTEM_ERRORS: Table = &[
+    (0, "AccountAlreadyInUse", "an account with the same address already exists"),
+    (1, "ResultWithNegativeLamports", "account does not have enough SOL to perform the operation"),
+    (2, "InvalidProgramId", "cannot assign account to this program id"),
+    (3, "InvalidAccountDataLength", "cannot allocate account data of this length"),
+    (4, "MaxSeedLengthExceeded", "length of requested seed is too long"),
+    (5, "AddressWithSeedMismatch", "provided address does not match addressed derived from seed"),
+    (6, "NonceNoRecentBlockhashes", "advancing stored nonce requires a populated RecentBlockhashes sysvar"),
+    (7, "NonceBlockhashNotExpired", "stored nonce is still in recent_blockhashes"),
+    (8, "NonceUnexpectedBlockhashValue", "specified nonce does not match stored nonce"),
+];
+
+/// The `TokenError` of the Token program, Token-2022 extends it.
+#[rustfmt::skip]
+const TOKEN_ERRORS: Table = &[
+    (0, "NotRentExempt", "Lamport balance below rent-exempt threshold"),
+    (1, "InsufficientFunds", "Insufficient funds"),
+    (2, "InvalidMint", "Invalid Mint"),
+    (3, "MintMismatch", "Account not associated with this Mint"),
+    (4, "OwnerMismatch", "Owner does not match"),
+    (5, "FixedSupply", "Fixed supply"),
+    (6, "AlreadyInUse", "Already in use"),
+    (7, "InvalidNumberOfProvidedSigners", "Invalid number of provided signers"),
+    (8, "InvalidNumberOfRequiredSigners", "Invalid number of required signers"),
+    (9, "UninitializedState", "State is uninitialized"),
+    (10, "NativeNotSupported", "Instruction does not support native tokens"),
+    (11, "NonNativeHasBalance", "Non-native account can only be closed if its balance is zero"),
+    (12, "InvalidInstruction", "Invalid instruction"),
+    (13, "InvalidState", "State is invalid for requested operation"),
+    (14, "Overflow", "Operation overflowed"),
+    (15, "AuthorityTypeNotSupported", "Account does not support specified authority type"),
+    (16, "MintCannotFreeze", "This token mint cannot freeze accounts"),
+    (17, "AccountFrozen", "Account is frozen"),
+    (18, "MintDecimalsMismatch", "The provided decimals value different from the Mint decimals"),
+    (19, "NonNativeNotSupported", "Instruction does not support non-native tokens"),
+];
+
+/// The `TokenError` codes added by Token-2022.
+#[rustfmt::skip]
+const TOKEN_2022_ERRORS: Table = &[
+    (20, "ExtensionTypeMismatch", "Extension type does not match already existing extensions"),
+    (21, "ExtensionBaseMismatch", "Extension does not match the base type provided"),
+    (22, "ExtensionAlreadyInitialized", "Extension already initialized on this account"),
+    (23, "ConfidentialTransferAccountHasBalance", "An account can only be closed if its confidential balance is zero"),
+    (24, "ConfidentialTransferAccountNotApproved", "Account not approved for confidential transfers"),
+    (25, "ConfidentialTransferDepositsAndTransfersDisabled", "Account not accepting deposits or transfers"),
+    (26, "ConfidentialTransferElGamalPubkeyMismatch", "ElGamal public key mismatch"),
+    (27, "ConfidentialTransferBalanceMismatch", "Balance mismatch"),
+    (28, "MintHasSupply", "Mint has non-zero supply. Burn all tokens before closing the mint"),
+    (29, "NoAuthorityExists", "No authority exists to perform the desired operation"),
+    (30, "TransferFeeExceedsMaximum", "Transfer fee exceeds maximum of 10,000 basis points"),
+    (31, "MintRequiredForTransfer", "Mint required for this account to transfer tokens, use `transfer_checked` or `transfer_checked_with_fee`"),
+    (32, "FeeMismatch", "Calculated fee does not match expected fee"),
+    (33, "FeeParametersMismatch", "Fee parameters associated with confidential transfer zero-knowledge proofs do not match fee parameters in mint"),
+    (34, "ImmutableOwner", "The owner authority cannot be changed"),
+    (35, "AccountHasWithheldTransferFees", "An account can only be closed if its withheld fee balance is zero, harvest fees to the mint and try again"),
+    (36, "NoMemo", "No memo in previous instruction; required for recipient to receive a transfer"),
+    (37, "NonTransferable", "Transfer is disabled for this mint"),
+    (38, "NonTransferableNeedsImmutableOwnership", "Non-transferable tokens can't be minted to an account without immutable ownership"),
+    (39, "MaximumPendingBalanceCounterExceeded", "The total number of `Deposit` and `Transfer` instructions to an account cannot exceed the associated `maximum_pending_balance_credit_counter`"),
+    (40, "MaximumDepositAmountExceeded", "Deposit amount exceeds maximum limit"),
+    (41, "CpiGuardSettingsLocked", "CPI Guard cannot be enabled or disabled in CPI"),
+    (42, "CpiGuardTransferBlocked", "CPI Guard is enabled, and a program attempted to transfer user funds via CPI without using a delegate"),
+    (43, "CpiGuardBurnBlocked", "CPI Guard is enabled, and a program attempted to burn user funds via CPI without using a delegate"),
+    (44, "CpiGuardCloseAccountBlocked", "CPI Guard is enabled, and a program attempted to close an account via CPI without returning lamports to owner"),
+    (45, "CpiGuardApproveBlocked", "CPI Guard is enabled, and a program attempted to approve a delegate"),
+    (46, "CpiGuardSetAuthorityBlocked", "CPI Guard is enabled, and a program attempted to add or replace an authority"),
+    (47, "CpiGuardOwnerChangeBlocked", "Account ownership cannot be changed while CPI Guard is enabled"),
+    (48, "ExtensionNotFound", "Extension not found in account data"),
+    (49, "NonConfidentialTransfersDisabled", "Non-confidential transfers disabled"),
+    (50, "ConfidentialTransferFeeAccountHasWithheldFee", "An account can only be closed if the confidential withheld fee is zero"),
+    (51, "InvalidExtensionCombination", "A mint or an account is initialized to an invalid combination of extensions"),
+    (52, "InvalidLengthForAlloc", "Extension allocation with overwrite must use the same length"),
+    (53, "AccountDecryption", "Failed to decrypt a confidential transfer account"),
+    (54, "ProofGeneration", "Failed to generate proof"),
+    (55, "InvalidProofInstructionOffset", "An invalid proof instruction offset was provided"),
+    (56, "HarvestToMintDisabled", "Harvest of withheld tokens to mint is disabled"),
+    (57, "SplitProofContextStateAccountsNotSupported", "Split proof context state accounts not supported for instruction"),
+    (58, "NotEnoughProofContextStateAccounts", "Not enough proof context state accounts provided"),
+    (59, "MalformedCiphertext", "Ciphertext is malformed"),
+    (60, "CiphertextArithmeticFailed", "Ciphertext arithmetic failed"),
+];
+
+/// The framework errors of Anchor 0.30, raised by every Anchor program below its own codes (6000+).
+#[rustfmt::skip]
+const ANCHOR_ERRORS: Table = &[
+    (100, "InstructionMissing", "Instruction discriminator not provided"),
+    (101, "InstructionFallbackNotFound", "Fallback functions are not supported"),
+    (102, "InstructionDidNotDeserialize", "The program could not deserialize the given instruction"),
+    (103, "InstructionDidNotSerialize", "The program could not serialize the given instruction"),
+    (1000, "IdlInstructionStub", "The program was compiled without idl instructions"),
+    (1001, "IdlInstructionInvalidProgram", "Invalid program given to the IDL instruction"),
+    (1002, "IdlAccountNotEmpty", "IDL account must be empty in order to resize, try closing first"),
+    (1500, "EventInstructionStub", "The program was compiled without `event-cpi` feature"),
+    (2000, "ConstraintMut", "A mut constraint was violated"),
+    (2001, "ConstraintHasOne", "A has one constraint was violated"),
+    (2002, "ConstraintSigner", "A signer constraint was violated"),
+    (2003, "ConstraintRaw", "A raw constraint was violated"),
+    (2004, "ConstraintOwner", "An owner constraint was violated"),
+    (2005, "ConstraintRentExempt", "A rent exemption constraint was violated"),
+    (2006, "ConstraintSeeds", "A seeds constraint was violated"),
+    (2007, "ConstraintExecutable", "An executable constraint was violated"),
+    (2008, "ConstraintState", "Deprecated Error, feel free to replace with something else"),
+    (2009, "ConstraintAssociated", "An associated constraint was violated"),
+    (2010, "ConstraintAssociatedInit", "An associated init constraint was violated"),
+    (2011, "ConstraintClose", "A close constraint was violated"),
+    (2012, "ConstraintAddress", "An address constraint was violated"),
+    (2013, "ConstraintZero", "Expected zero account discriminant"),
+    (2014, "ConstraintTokenMint", "A token mint constraint was violated"),
+    (2015, "ConstraintTokenOwner", "A token owner constraint was violated"),
+    (2016, "ConstraintMintMintAuthority", "A mint mint authority constraint was violated"),
+    (2017, "ConstraintMintFreezeAuthority", "A mint freeze authority constraint was violated"),
+    (2018, "ConstraintMintDecimals", "A mint decimals constraint was violated"),
+    (2019, "ConstraintSpace", "A space constraint was violated"),
+    (2020, "ConstraintAccountIsNone", "A required account for the constraint is None"),
+    (2021, "ConstraintTokenTokenProgram", "A token account token program constraint was violated"),
+    (2022, "ConstraintMintTokenProgram", "A mint token program constraint was violated"),
+    (2023, "ConstraintAssociatedTokenTokenProgram", "An associated token account token program constraint was violated"),
+    (2024, "ConstraintMintGroupPointerExtension", "A group pointer extension constraint was violated"),
+    (2025, "ConstraintMintGroupPointerExtensionAuthority", "A group pointer extension authority constraint was violated"),
+    (2026, "ConstraintMintGroupPointerExtensionGroupAddress", "A group pointer extension group address constraint was violated"),
+    (2027, "ConstraintMintGroupMemberPointerExtension", "A group member pointer extension constraint was violated"),
+    (2028, "ConstraintMintGroupMemberPointerExtensionAuthority", "A group member pointer extension authority constraint was violated"),
+    (2029, "ConstraintMintGroupMemberPointerExtensionMemberAddress", "A group member pointer extension group address constraint was violated"),
+    (2030, "ConstraintMintMetadataPointerExtension", "A metadata pointer extension constraint was violated"),
+    (2031, "ConstraintMintMetadataPointerExtensionAuthority", "A metadata pointer extension authority constraint was violated"),
+    (2032, "ConstraintMintMetadataPointerExtensionMetadataAddress", "A metadata pointer extension metadata address constraint was violated"),
+    (2033, "ConstraintMintCloseAuthorityExtension", "A close authority constraint was violated"),
+    (2034, "ConstraintMintCloseAuthorityExtensionAuthority", "A close authority extension authority constraint was violated"),
+    (2035, "ConstraintMintPermanentDelegateExtension", "A permanent delegate extension constraint was violated"),
+    (2036, "ConstraintMintPermanentDelegateExtensionDelegate", "A permanent delegate extension authority constraint was violated"),
+    (2037, "ConstraintMintTransferHookExtension", "A transfer hook extension constraint was violated"),
+    (2038, "ConstraintMintTransferHookExtensionAuthority", "A transfer hook extension authority constraint was violated"),
+    (2039, "ConstraintMintTransferHookExtensionProgramId", "A transfer hook extension transfer hook program id constraint was violated"),
+    (2500, "RequireViolated", "A require expression was violated"),
+    (2501, "RequireEqViolated", "A require_eq expression was violated"),
+    (2502, "RequireKeysEqViolated", "A require_keys_eq expression was violated"),
+    (2503, "RequireNeqViolated", "A require_neq expression was violated"),
+    (2504, "RequireKeysNeqViolated", "A require_keys_neq expression was violated"),
+    (2505, "RequireGtViolated", "A require_gt expression was violated"),
+    (2506, "RequireGteViolated", "A require_gte expression was violated"),
+    (3000, "AccountDiscriminatorAlreadySet", "The account discriminator was already set on this account"),
+    (3001, "AccountDiscriminatorNotFound", "No discriminator was found on the account"),
+    (3002, "AccountDiscriminatorMismatch", "Account discriminator did not match what was expected"),
+    (3003, "AccountDidNotDeserialize", "Failed to deserialize the account"),
+    (3004, "AccountDidNotSerialize", "Failed to serialize the account"),
+    (3005, "AccountNotEnoughKeys", "Not enough account keys given to the instruction"),
+    (3006, "AccountNotMutable", "The given account is not mutable"),
+    (3007, "AccountOwnedByWrongProgram", "The given account is owned by a different program than expected"),
+    (3008, "InvalidProgramId", "Program ID was not as expected"),
+    (3009, "InvalidProgramExecutable", "Program account is not executable"),
+    (3010, "AccountNotSigner", "The given account did not sign"),
+    (3011, "AccountNotSystemOwned", "The given account is not owned by the system program"),
+    (3012, "AccountNotInitialized", "The program expected this account to be already initialized"),
+    (3013, "AccountNotProgramData", "The given account is not a program data account"),
+    (3014, "AccountNotAssociatedTokenAccount", "The given account is not the associated token account"),
+    (3015, "AccountSysvarMismatch", "The given public key does not match the required sysvar"),
+    (3016, "AccountReallocExceedsLimit", "The account reallocation exceeds the MAX_PERMITTED_DATA_INCREASE limit"),
+    (3017, "AccountDuplicateReallocs", "The account was duplicated for more than one reallocation"),
+    (4100, "DeclaredProgramIdMismatch", "The declared program id does not match the actual program id"),
+    (4101, "TryingToInitPayerAsProgramAccount", "You cannot/should not initialize the payer account as a program account"),
+    (4102, "InvalidNumericConversion", "The program could not perform the numeric conversion, out of range integral type conversion attempted"),
+    (5000, "Deprecated", "The API being used is deprecated and should no longer be used"),
+];
+
+/// The first code of the errors declared by Anchor programs, the codes below are the framework
+/// ones.
+pub const ANCHOR_ERROR_CODE_OFFSET: u32 = 6000;
+
+fn lookup(table: Table, code: u32) -> Option<CustomError> {
+    let index = table.binary_search_by_key(&code, |(code, ..)| *code).ok()?;
+    let (code, name, message) = table[index];
+    Some(CustomError::new(code, name, message))
+}
+
+/// Returns the built-in error `code` of the System, Token or Token-2022 program.
+pub fn builtin_error(program_id: &Pubkey, code: u32) -> Option<CustomError> {
+    match *program_id {
+        SYSTEM_PROGRAM_ID => lookup(SYSTEM_ERRORS, code),
+        TOKEN_PROGRAM_ID => lookup(TOKEN_ERRORS, code),
+        TOKEN_2022_PROGRAM_ID => {
+            lookup(TOKEN_ERRORS, code).or_else(|| lookup(TOKEN_2022_ERRORS, code))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the Anchor framework error `code`, raised by any Anchor program.
+///
+/// Other programs may use the same codes for their own errors, see
+/// [`Registry::register_anchor_program`](crate::registry::Registry::register_anchor_program).
+pub fn anchor_error(code: u32) -> Option<CustomError> {
+    lookup(ANCHOR_ERRORS, code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{registry::Registry, TransactionsParser};
+
+    #[test]
+    fn test_builtin_errors() {
+        for table in [
+            SYSTEM_ERRORS,
+            TOKEN_ERRORS,
+            TOKEN_2022_ERRORS,
+            ANCHOR_ERRORS,
+        ] {
+            assert!(table.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        }
+
+        assert_eq!(
+            builtin_error(&[0; 32], 1).unwrap().name,
+            "ResultWithNegativeLamports"
+        );
+        assert_eq!(
+            builtin_error(&TOKEN_PROGRAM_ID, 1),
+            Some(CustomError::new(
+                1,
+                "InsufficientFunds",
+                "Insufficient funds"
+            ))
+        );
+        assert_eq!(builtin_error(&TOKEN_PROGRAM_ID, 37), None);
+        assert_eq!(
+            builtin_error(&TOKEN_2022_PROGRAM_ID, 37).unwrap().name,
+            "NonTransferable"
+        );
+        assert_eq!(
+            builtin_error(&TOKEN_2022_PROGRAM_ID, 4).unwrap().name,
+            "OwnerMismatch"
+        );
+        assert_eq!(builtin_error(&[0x0c; 32], 0), None);
+
+        assert_eq!(anchor_error(2003).unwrap().name, "ConstraintRaw");
+        assert_eq!(anchor_error(3012).unwrap().name, "AccountNotInitialized");
+        assert_eq!(anchor_error(6000), None);
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let parser = TransactionsParser::with_registry(
+            Registry::builder()
+                .error(
+                    TOKEN_PROGRAM_ID,
+                    CustomError::new(1, "NotEnoughTokens", "Not enough tokens"),
+                )
+                .build(),
+        );
+        parser
+            .register_idl(include_str!("../fixtures/idl/vault.json"))
+            .unwrap();
+
+        assert_eq!(
+            parser.resolve_error(&[0x0c; 32], 6000),
+            Some(CustomError::new(
+                6000,
+                "InsufficientFunds",
+                "Insufficient funds"
+            ))
+        );
+        assert_eq!(
+            parser.resolve_error(&[0x0c; 32], 2006).unwrap().name,
+            "ConstraintSeeds"
+        );
+        assert_eq!(parser.resolve_error(&[0x0c; 32], 6001), None);
+
+        // Registered errors take precedence over the built-in ones.
+        assert_eq!(
+            parser.resolve_error(&TOKEN_PROGRAM_ID, 1).unwrap().name,
+            "NotEnoughTokens"
+        );
+        assert_eq!(
+            parser.resolve_error(&TOKEN_PROGRAM_ID, 4).unwrap().name,
+            "OwnerMismatch"
+        );
+
+        // Only the programs built with Anchor raise the framework codes.
+        assert_eq!(parser.resolve_error(&SYSTEM_PROGRAM_ID, 2006), None);
+        assert_eq!(parser.resolve_error(&[0x0d; 32], 2006), None);
+        parser.register_anchor_program([0x0d; 32]);
+        assert_eq!(
+            parser.resolve_error(&[0x0d; 32], 2006).unwrap().name,
+            "ConstraintSeeds"
+        );
+        assert_eq!(
+            parser.resolve_error(&[0x0d; 32], 2039).unwrap().name,
+            "ConstraintMintTransferHookExtensionProgramId"
+        );
+
+        parser
+            .register_idl_with_id(
+                include_str!("../fixtures/idl/metadata_codama.json"),
+                [0x0e; 32],
+            )
+            .unwrap();
+        assert_eq!(
+            parser.resolve_error(&[0x0e; 32], 0).unwrap().name,
+            "instructionUnpackError"
+        );
+        assert_eq!(parser.resolve_error(&[0x0e; 32], 2006), None);
+        assert_eq!(parser.resolve_error(&[0x0e; 32], 101), None);
+    }
+}
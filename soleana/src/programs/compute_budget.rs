@@ -18,22 +18,45 @@ impl Program for ComputeBudget {
         data: &[u8],
         _: &[Pubkey],
     ) -> SoleanaResult<Self::Instructions> {
-        match data[0..1].to_vec()[..] {
-            [0x02] => Ok(ComputeBudgetInstructions::SetComputeUnitLimit {
-                units: u32::from_le_bytes(data[1..5].try_into().unwrap()),
+        let mut data = DataCursor::new(data);
+        match data.read_u8()? {
+            0x02 => Ok(ComputeBudgetInstructions::SetComputeUnitLimit {
+                units: data.read_u32()?,
             }),
-            [0x03] => Ok(ComputeBudgetInstructions::SetComputeUnitPrice {
-                micro_lamports: u64::from_le_bytes(data[1..9].try_into().unwrap()),
+            0x03 => Ok(ComputeBudgetInstructions::SetComputeUnitPrice {
+                micro_lamports: data.read_u64()?,
             }),
-            _ => Err(SoleanaError::InvalidInstruction),
+            _ => Err(SoleanaError::UnknownInstruction),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ComputeBudgetInstructions {
     SetComputeUnitLimit { units: u32 },
     SetComputeUnitPrice { micro_lamports: u64 },
 }
 
-impl ProgramInstructions for ComputeBudgetInstructions {}
+impl ProgramInstructions for ComputeBudgetInstructions {
+    fn program_name(&self) -> &str {
+        "ComputeBudget"
+    }
+
+    fn instruction_name(&self) -> &str {
+        match self {
+            ComputeBudgetInstructions::SetComputeUnitLimit { .. } => "SetComputeUnitLimit",
+            ComputeBudgetInstructions::SetComputeUnitPrice { .. } => "SetComputeUnitPrice",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            ComputeBudgetInstructions::SetComputeUnitLimit { units } => {
+                Value::Struct(vec![("units".to_string(), Value::U32(*units))])
+            }
+            ComputeBudgetInstructions::SetComputeUnitPrice { micro_lamports } => Value::Struct(
+                vec![("micro_lamports".to_string(), Value::U64(*micro_lamports))],
+            ),
+        }
+    }
+}
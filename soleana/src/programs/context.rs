@@ -0,0 +1,214 @@
+use crate::{
+    error::{SoleanaError, SoleanaResult},
+    types::{Header, Indicator, Pubkey, RawInstruction, LUT},
+    value::LabeledAccount,
+};
+
+/// Equivalent to the `AccountMeta` from the solana-instruction. <https://docs.rs/solana-instruction/latest/solana_instruction/struct.AccountMeta.html>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// A trait for structs holding the named accounts of an instruction.
+pub trait Accounts: Sized {
+    /// Builds the struct from the accounts of the instruction, in order.
+    ///
+    /// Optional accounts are `None` when missing, or when set to `program_id` as Anchor does.
+    fn from_accounts(program_id: Pubkey, accounts: &[Pubkey]) -> SoleanaResult<Self>;
+
+    /// The accounts labeled with their names, missing optional accounts are skipped.
+    fn labeled(&self) -> Vec<LabeledAccount>;
+}
+
+/// [`ParseContext`] is everything a parser can see about the instruction it's parsing.
+///
+/// Besides the instruction itself, it exposes the signer and writable flags of the accounts,
+/// the position of the instruction, the other top-level instructions and the message version.
+///
+/// Inner instructions are parsed with the context of the message, the top-level instructions
+/// included.
+#[derive(Debug, Clone, Copy)]
+pub struct ParseContext<'a> {
+    /// The instruction being parsed.
+    pub instruction: &'a RawInstruction,
+    /// The position of the instruction in the top-level instructions, the position of the
+    /// invoking instruction for inner instructions.
+    pub instruction_index: usize,
+    /// The invocation depth of the instruction, see
+    /// [`Instruction::stack_height`](crate::types::Instruction::stack_height).
+    pub stack_height: Option<u32>,
+    /// Every top-level instruction of the message, including the one being parsed.
+    pub instructions: &'a [RawInstruction],
+    /// The account keys of the message, followed by the accounts loaded from the luts.
+    pub accounts: &'a [Pubkey],
+    /// The number of static account keys at the start of `accounts`.
+    pub static_accounts_len: usize,
+    /// The luts of the message, if any.
+    pub luts: Option<&'a [LUT]>,
+    pub header: &'a Header,
+    pub version: Indicator,
+}
+
+impl<'a> ParseContext<'a> {
+    /// The program id of the instruction being parsed.
+    pub fn program_id(&self) -> Pubkey {
+        self.instruction.program_id
+    }
+
+    /// The data of the instruction being parsed.
+    pub fn data(&self) -> &'a [u8] {
+        &self.instruction.data
+    }
+
+    /// Returns the pubkey of the `index`-th account of the instruction.
+    pub fn account(&self, index: usize) -> Option<Pubkey> {
+        self.instruction
+            .account_indexes
+            .get(index)
+            .and_then(|&i| self.accounts.get(i as usize))
+            .copied()
+    }
+
+    /// Returns the pubkeys of every account of the instruction.
+    ///
+    /// Fails if an account can't be resolved, for example when it comes from a lut that isn't registered.
+    pub fn instruction_accounts(&self) -> SoleanaResult<Vec<Pubkey>> {
+        self.instruction
+            .account_indexes
+            .iter()
+            .map(|&i| {
+                self.accounts
+                    .get(i as usize)
+                    .copied()
+                    .ok_or(SoleanaError::InvalidAccountIndex)
+            })
+            .collect()
+    }
+
+    /// Returns the accounts of the instruction with their signer and writable flags.
+    ///
+    /// Accounts whose index can't be resolved (from a lut that isn't registered) are skipped.
+    pub fn account_metas(&self) -> Vec<AccountMeta> {
+        self.instruction
+            .account_indexes
+            .iter()
+            .filter_map(|&i| {
+                let i = i as usize;
+                Some(AccountMeta {
+                    pubkey: *self.accounts.get(i)?,
+                    is_signer: self.is_signer(i),
+                    is_writable: self.is_writable(i),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns `true` if the account at `index` of the message accounts is a signer.
+    pub fn is_signer(&self, index: usize) -> bool {
+        index < self.header.num_required_signatures as usize
+    }
+
+    /// Returns `true` if the account at `index` of the message accounts is writable.
+    ///
+    /// Follows the message layout: writable signers, readonly signers, writable and readonly
+    /// unsigned static accounts, then the writable and readonly accounts loaded from the luts.
+    pub fn is_writable(&self, index: usize) -> bool {
+        let num_signers = self.header.num_required_signatures as usize;
+
+        if index < num_signers {
+            index < num_signers.saturating_sub(self.header.num_readonly_signed_accounts as usize)
+        } else if index < self.static_accounts_len {
+            index
+                < self
+                    .static_accounts_len
+                    .saturating_sub(self.header.num_readonly_unsigned_accounts as usize)
+        } else {
+            let num_writable_loaded: usize = self
+                .luts
+                .unwrap_or_default()
+                .iter()
+                .map(|lut| lut.writable_indexes.len())
+                .sum();
+            index - self.static_accounts_len < num_writable_loaded
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prelude::program_impl::*, registry::Registry, TransactionsParser};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Seen {
+        instruction_index: usize,
+        instructions_len: usize,
+        metas: Vec<AccountMeta>,
+    }
+
+    impl ProgramInstructions for Seen {}
+
+    struct Introspect;
+
+    impl Program for Introspect {
+        fn program_id() -> Pubkey {
+            [0x0d; 32]
+        }
+
+        type Instructions = Seen;
+
+        fn parse_instruction(
+            _: Pubkey,
+            _: &Vec<u8>,
+            _: &[u8],
+            _: &[Pubkey],
+        ) -> SoleanaResult<Self::Instructions> {
+            // Only parsed with its context.
+            Err(SoleanaError::InvalidInstruction)
+        }
+
+        fn parse_with_context(context: &ParseContext) -> SoleanaResult<Self::Instructions> {
+            Ok(Seen {
+                instruction_index: context.instruction_index,
+                instructions_len: context.instructions.len(),
+                metas: context.account_metas(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_parse_context() {
+        // A writable and a readonly signer, a writable and a readonly unsigned account.
+        let transaction = format!(
+            "02{}{}{}{}",
+            "00".repeat(128),
+            "02010104",
+            "0a".repeat(32) + &"0b".repeat(32) + &"0c".repeat(32) + &"0d".repeat(32),
+            "00".repeat(32) + "02030000" + "030400010203" + "00",
+        );
+
+        let parser =
+            TransactionsParser::with_registry(Registry::builder().program::<Introspect>().build());
+        let transaction = parser.parse_transaction(&transaction).unwrap();
+        let parsed = transaction.message.instructions[1].parsed.as_ref().unwrap();
+
+        let meta = |key: u8, is_signer, is_writable| AccountMeta {
+            pubkey: [key; 32],
+            is_signer,
+            is_writable,
+        };
+        let expected = Seen {
+            instruction_index: 1,
+            instructions_len: 2,
+            metas: vec![
+                meta(0x0a, true, true),
+                meta(0x0b, true, false),
+                meta(0x0c, false, true),
+                meta(0x0d, false, false),
+            ],
+        };
+        assert_eq!(parsed.downcast_ref::<Seen>(), Some(&expected));
+    }
+}
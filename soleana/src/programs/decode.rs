@@ -0,0 +1,260 @@
+use crate::{
+    error::{SoleanaError, SoleanaResult},
+    reader::Reader,
+    types::Pubkey,
+};
+
+/// A cursor over instruction data.
+///
+/// Every read is checked and advances the cursor, integers are little-endian and the other types
+/// follow the borsh layout, see [`Decode`].
+///
+/// ```
+/// use soleana::prelude::program_impl::*;
+///
+/// let mut data = DataCursor::new(&[0x02, 0xe8, 0x03, 0x00, 0x00]);
+/// assert_eq!(data.read_u8(), Ok(0x02));
+/// assert_eq!(data.read_u32(), Ok(1000));
+/// assert_eq!(data.finish(), Ok(()));
+/// ```
+pub struct DataCursor<'a> {
+    reader: Reader<'a>,
+}
+
+macro_rules! impl_read_le {
+    ($($name:ident => $ty:ty),*) => {
+        $(
+            #[doc = concat!("Reads a little-endian `", stringify!($ty), "`.")]
+            pub fn $name(&mut self) -> SoleanaResult<$ty> {
+                self.read_array().map(<$ty>::from_le_bytes)
+            }
+        )*
+    };
+}
+
+impl<'a> DataCursor<'a> {
+    /// Creates a cursor at the start of `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            reader: Reader::new(data),
+        }
+    }
+
+    /// Returns the number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.reader.remaining().len()
+    }
+
+    /// Returns `true` if all the data was read.
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Checks that all the data was read, the error carries the number of bytes left over.
+    pub fn finish(self) -> SoleanaResult<()> {
+        match self.remaining() {
+            0 => Ok(()),
+            left => Err(SoleanaError::TrailingBytes(left)),
+        }
+    }
+
+    /// Reads the next `count` bytes.
+    pub fn read_bytes(&mut self, count: usize) -> SoleanaResult<&[u8]> {
+        self.reader.read_bytes(count)
+    }
+
+    /// Reads the next `N` bytes into an array.
+    pub fn read_array<const N: usize>(&mut self) -> SoleanaResult<[u8; N]> {
+        self.read_bytes(N)?
+            .try_into()
+            .map_err(|_| SoleanaError::NotEnoughBytes)
+    }
+
+    /// Reads a single byte.
+    pub fn read_u8(&mut self) -> SoleanaResult<u8> {
+        self.reader.read_byte()
+    }
+
+    impl_read_le!(
+        read_u16 => u16, read_u32 => u32, read_u64 => u64, read_u128 => u128,
+        read_i8 => i8, read_i16 => i16, read_i32 => i32, read_i64 => i64, read_i128 => i128,
+        read_f32 => f32, read_f64 => f64
+    );
+
+    /// Reads a `bool`, rejecting any byte other than 0 or 1.
+    pub fn read_bool(&mut self) -> SoleanaResult<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SoleanaError::InvalidInstruction),
+        }
+    }
+
+    /// Reads a 32-byte public key.
+    pub fn read_pubkey(&mut self) -> SoleanaResult<Pubkey> {
+        self.read_array()
+    }
+
+    /// Reads a compact u16, the length prefix used by the transaction format.
+    pub fn read_compact_u16(&mut self) -> SoleanaResult<u16> {
+        self.reader.read_compact_u16()
+    }
+
+    /// Reads the variant index of a borsh enum.
+    pub fn read_variant(&mut self) -> SoleanaResult<u8> {
+        self.read_u8()
+    }
+
+    /// Decodes a value implementing [`Decode`].
+    pub fn read<T: Decode>(&mut self) -> SoleanaResult<T> {
+        T::decode(self)
+    }
+
+    /// Reads a borsh `Option`: a 0/1 tag followed by the value.
+    pub fn read_option<T: Decode>(&mut self) -> SoleanaResult<Option<T>> {
+        match self.read_u8()? {
+            0 => Ok(None),
+            1 => self.read().map(Some),
+            _ => Err(SoleanaError::InvalidInstruction),
+        }
+    }
+
+    /// Reads a borsh `Vec`: a u32 length followed by the items.
+    pub fn read_vec<T: Decode>(&mut self) -> SoleanaResult<Vec<T>> {
+        let len = self.read_u32()? as usize;
+        // Every item takes at least a byte, a longer length can't be valid.
+        if len > self.remaining() && std::mem::size_of::<T>() > 0 {
+            return Err(SoleanaError::NotEnoughBytes);
+        }
+
+        (0..len).map(|_| self.read()).collect()
+    }
+
+    /// Reads a borsh `String`: a u32 length followed by the utf-8 bytes.
+    pub fn read_string(&mut self) -> SoleanaResult<String> {
+        let len = self.read_u32()? as usize;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| SoleanaError::InvalidInstruction)
+    }
+}
+
+/// A trait for values that can be decoded from instruction data.
+///
+/// Integers are little-endian, the other types follow the borsh layout: `bool` is a single byte,
+/// `Option<T>` a 0/1 tag, `Vec<T>` and `String` a u32 length prefix and arrays have no prefix.
+/// Borsh enums are a variant index followed by the fields of the variant:
+///
+/// ```
+/// use soleana::prelude::program_impl::*;
+///
+/// enum Side {
+///     Bid,
+///     Ask { limit: u64 },
+/// }
+///
+/// impl Decode for Side {
+///     fn decode(data: &mut DataCursor) -> SoleanaResult<Self> {
+///         match data.read_variant()? {
+///             0 => Ok(Side::Bid),
+///             1 => Ok(Side::Ask { limit: data.read()? }),
+///             _ => Err(SoleanaError::InvalidInstruction),
+///         }
+///     }
+/// }
+///
+/// assert!(matches!(Side::from_slice(&[1, 5, 0, 0, 0, 0, 0, 0, 0]), Ok(Side::Ask { limit: 5 })));
+/// ```
+pub trait Decode: Sized {
+    /// Decodes a value at the position of the cursor, advancing it past the decoded bytes.
+    fn decode(data: &mut DataCursor) -> SoleanaResult<Self>;
+
+    /// Decodes a value from the whole of `data`, rejecting any trailing bytes.
+    fn from_slice(data: &[u8]) -> SoleanaResult<Self> {
+        let mut cursor = DataCursor::new(data);
+        let value = Self::decode(&mut cursor)?;
+        cursor.finish()?;
+        Ok(value)
+    }
+}
+
+macro_rules! impl_decode {
+    ($($ty:ty => $read:ident),*) => {
+        $(
+            impl Decode for $ty {
+                fn decode(data: &mut DataCursor) -> SoleanaResult<Self> {
+                    data.$read()
+                }
+            }
+        )*
+    };
+}
+
+impl_decode!(
+    u8 => read_u8, u16 => read_u16, u32 => read_u32, u64 => read_u64, u128 => read_u128,
+    i8 => read_i8, i16 => read_i16, i32 => read_i32, i64 => read_i64, i128 => read_i128,
+    f32 => read_f32, f64 => read_f64, bool => read_bool, String => read_string
+);
+
+impl<T: Decode, const N: usize> Decode for [T; N] {
+    fn decode(data: &mut DataCursor) -> SoleanaResult<Self> {
+        let items = (0..N)
+            .map(|_| T::decode(data))
+            .collect::<SoleanaResult<Vec<T>>>()?;
+        items
+            .try_into()
+            .map_err(|_| SoleanaError::InvalidInstruction)
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(data: &mut DataCursor) -> SoleanaResult<Self> {
+        data.read_option()
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(data: &mut DataCursor) -> SoleanaResult<Self> {
+        data.read_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::program_impl::*;
+
+    #[test]
+    fn test_decode_primitives() {
+        let mut data = DataCursor::new(&[1, 0x2a, 0, 3, 0, 0, 0, b'a', b'b', b'c', 7, 0xff]);
+
+        assert_eq!(data.read::<Option<u16>>(), Ok(Some(42)));
+        assert_eq!(data.read_string().as_deref(), Ok("abc"));
+        assert_eq!(data.read::<[u8; 1]>(), Ok([7]));
+        assert_eq!(data.read_i8(), Ok(-1));
+        assert_eq!(data.read_u8(), Err(SoleanaError::NotEnoughBytes));
+        assert_eq!(data.finish(), Ok(()));
+
+        // A length larger than the remaining data is rejected before allocating.
+        assert_eq!(
+            Vec::<u8>::from_slice(&[0xff, 0xff, 0xff, 0xff, 0]),
+            Err(SoleanaError::NotEnoughBytes)
+        );
+        assert_eq!(
+            Option::<bool>::from_slice(&[2]),
+            Err(SoleanaError::InvalidInstruction)
+        );
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        assert_eq!(u16::from_slice(&[1, 0]), Ok(1));
+        assert_eq!(
+            u16::from_slice(&[1, 0, 0]),
+            Err(SoleanaError::TrailingBytes(1))
+        );
+
+        let mut data = DataCursor::new(&[0x80, 0x01, 0x0a]);
+        assert_eq!(data.read_compact_u16(), Ok(0x80));
+        assert_eq!(data.remaining(), 1);
+        assert_eq!(data.finish(), Err(SoleanaError::TrailingBytes(1)));
+    }
+}
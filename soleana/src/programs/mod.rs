@@ -1,4 +1,17 @@
-use crate::{error::SoleanaResult, types::Pubkey};
+use crate::{
+    error::{SoleanaError, SoleanaResult},
+    types::{Cluster, Pubkey},
+    value::{LabeledAccount, Value},
+};
+
+use context::ParseContext;
+use std::{any::Any, marker::PhantomData};
+
+/// Context given to the parsers.
+pub mod context;
+
+/// Decoding of instruction data.
+pub mod decode;
 
 /// System program.
 pub mod system;
@@ -6,14 +19,40 @@ pub mod system;
 /// Compute budget program.
 pub mod compute_budget;
 
+/// The name returned by the default methods of [`ProgramInstructions`], it is never exposed as
+/// the name of an instruction or an event.
+pub(crate) const UNKNOWN_NAME: &str = "Unknown";
+
 /// A trait for programs that can be parsed by the parser.
 pub trait Program {
     fn program_id() -> Pubkey
     where
         Self: Sized;
 
+    /// The name of the program, defaults to the name of the type implementing it.
+    fn name() -> &'static str
+    where
+        Self: Sized,
+    {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// The program ids the program is deployed at on `cluster`.
+    ///
+    /// Defaults to [`Program::program_id`] on every cluster, override it for programs deployed
+    /// at different addresses on devnet or testnet.
+    fn program_ids(_cluster: &Cluster) -> Vec<Pubkey>
+    where
+        Self: Sized,
+    {
+        vec![Self::program_id()]
+    }
+
     type Instructions: ProgramInstructions;
 
+    /// Parses an instruction of the program from its ids, data and the message accounts.
+    #[allow(clippy::ptr_arg)]
     fn parse_instruction(
         program_id: Pubkey,
         ix_accounts: &Vec<u8>,
@@ -23,16 +62,195 @@ pub trait Program {
     where
         Self: Sized;
 
-    fn match_accounts(ix_accounts: &Vec<u8>, accounts: &[Pubkey]) -> Vec<Pubkey> {
-        ix_accounts.iter().map(|&i| accounts[i as usize]).collect()
+    /// Parses an instruction of the program with the full [`ParseContext`].
+    ///
+    /// Defaults to [`Program::parse_instruction`], override it to look at the rest of the message.
+    fn parse_with_context(context: &ParseContext) -> SoleanaResult<Self::Instructions>
+    where
+        Self: Sized,
+    {
+        Self::parse_instruction(
+            context.program_id(),
+            &context.instruction.account_indexes,
+            context.data(),
+            context.accounts,
+        )
+    }
+
+    /// Resolves the accounts of an instruction from the message accounts.
+    ///
+    /// Fails if an index is out of the message accounts.
+    #[allow(clippy::ptr_arg)]
+    fn match_accounts(ix_accounts: &Vec<u8>, accounts: &[Pubkey]) -> SoleanaResult<Vec<Pubkey>> {
+        ix_accounts
+            .iter()
+            .map(|&i| {
+                accounts
+                    .get(i as usize)
+                    .copied()
+                    .ok_or(SoleanaError::InvalidAccountIndex)
+            })
+            .collect()
+    }
+}
+
+/// A trait for parsers that carry their own state, registered as trait objects.
+///
+/// Unlike [`Program`], the parser is an instance so it can hold configuration built at runtime
+/// (a token-decimals map, an IDL, feature flags...). Closures are registered through
+/// [`Registry::register_parser_fn`](crate::registry::Registry::register_parser_fn).
+pub trait InstructionParser: Send + Sync {
+    /// The name of the program parsed by the parser, `None` when it isn't known, as for closures.
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Parses an instruction with the full [`ParseContext`].
+    ///
+    /// Return [`SoleanaError::UnknownInstruction`] for the instructions the parser doesn't know,
+    /// instructions are left unparsed on any error and the rest of the transaction is parsed.
+    fn parse_with_context(
+        &self,
+        context: &ParseContext,
+    ) -> SoleanaResult<Box<dyn ProgramInstructions>>;
+}
+
+impl<F> InstructionParser for F
+where
+    F: Fn(Pubkey, &Vec<u8>, &[u8], &[Pubkey]) -> SoleanaResult<Box<dyn ProgramInstructions>>
+        + Send
+        + Sync,
+{
+    fn parse_with_context(
+        &self,
+        context: &ParseContext,
+    ) -> SoleanaResult<Box<dyn ProgramInstructions>> {
+        self(
+            context.program_id(),
+            &context.instruction.account_indexes,
+            context.data(),
+            context.accounts,
+        )
+    }
+}
+
+/// Adapts a static [`Program`] to the [`InstructionParser`] trait.
+pub(crate) struct ProgramParser<P>(PhantomData<fn() -> P>);
+
+impl<P> ProgramParser<P> {
+    pub(crate) fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<P: Program> InstructionParser for ProgramParser<P>
+where
+    P::Instructions: ProgramInstructions + 'static,
+{
+    fn name(&self) -> Option<&str> {
+        Some(P::name())
+    }
+
+    fn parse_with_context(
+        &self,
+        context: &ParseContext,
+    ) -> SoleanaResult<Box<dyn ProgramInstructions>> {
+        let instruction = P::parse_with_context(context)?;
+        Ok(Box::new(instruction))
     }
 }
 
 /// A trait for instructions that can be parsed by the parser.
-pub trait ProgramInstructions: std::fmt::Debug {}
+///
+/// Parsed instructions are stored as `Box<dyn ProgramInstructions>`, use [`downcast_ref`](#method.downcast_ref)
+/// or [`Instruction::parsed_as`](crate::types::Instruction::parsed_as) to get the concrete type back.
+///
+/// Implementors must be `Clone + PartialEq + Eq` (usually derived), so parsed transactions can be
+/// cloned and compared through the [`DynInstructions`] facility.
+pub trait ProgramInstructions: std::fmt::Debug + Any + DynInstructions {
+    /// The name of the program the instructions belong to.
+    fn program_name(&self) -> &str {
+        UNKNOWN_NAME
+    }
+
+    /// The name of the instruction variant.
+    fn instruction_name(&self) -> &str {
+        UNKNOWN_NAME
+    }
+
+    /// The accounts of the instruction, labeled with their names.
+    fn accounts(&self) -> Vec<LabeledAccount> {
+        Vec::new()
+    }
+
+    /// The arguments of the instruction, as a [`Value::Struct`].
+    fn fields(&self) -> Value {
+        Value::empty()
+    }
+}
+
+/// Clone and equality for `dyn ProgramInstructions`, implemented for every `Clone + Eq` type.
+pub trait DynInstructions {
+    /// Clones the instructions into a new box.
+    fn clone_box(&self) -> Box<dyn ProgramInstructions>;
+
+    /// Compares the instructions with instructions of any type, different types are never equal.
+    fn dyn_eq(&self, other: &dyn ProgramInstructions) -> bool;
+}
+
+impl<T> DynInstructions for T
+where
+    T: ProgramInstructions + Clone + Eq,
+{
+    fn clone_box(&self) -> Box<dyn ProgramInstructions> {
+        Box::new(self.clone())
+    }
+
+    fn dyn_eq(&self, other: &dyn ProgramInstructions) -> bool {
+        other.downcast_ref::<T>() == Some(self)
+    }
+}
+
+impl Clone for Box<dyn ProgramInstructions> {
+    fn clone(&self) -> Self {
+        self.as_ref().clone_box()
+    }
+}
+
+impl PartialEq for dyn ProgramInstructions {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn ProgramInstructions {}
+
+impl dyn ProgramInstructions {
+    /// Returns `true` if the instructions are of type `T`.
+    pub fn is<T: ProgramInstructions>(&self) -> bool {
+        (self as &dyn Any).is::<T>()
+    }
+
+    /// Returns the instructions as `T`, if they are of type `T`.
+    pub fn downcast_ref<T: ProgramInstructions>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref::<T>()
+    }
+
+    /// Returns the boxed instructions as `T`, giving them back if they are of another type.
+    pub fn downcast<T: ProgramInstructions>(
+        self: Box<Self>,
+    ) -> Result<Box<T>, Box<dyn ProgramInstructions>> {
+        if self.is::<T>() {
+            let any: Box<dyn Any> = self;
+            Ok(any.downcast::<T>().expect("type checked above"))
+        } else {
+            Err(self)
+        }
+    }
+}
 
 /// Enum of all the instructions that can be given by the parser.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Instructions {
     /// System instructions.
     System(system::SystemInstructions),
@@ -41,3 +259,18 @@ pub enum Instructions {
     /// User defined Program instructions.
     Program(Box<dyn ProgramInstructions>),
 }
+
+impl From<Box<dyn ProgramInstructions>> for Instructions {
+    /// Sorts the parsed instructions of the built-in programs into their own variant.
+    fn from(parsed: Box<dyn ProgramInstructions>) -> Self {
+        let parsed = match parsed.downcast::<system::SystemInstructions>() {
+            Ok(system) => return Instructions::System(*system),
+            Err(parsed) => parsed,
+        };
+
+        match parsed.downcast::<compute_budget::ComputeBudgetInstructions>() {
+            Ok(compute_budget) => Instructions::ComputeBudget(*compute_budget),
+            Err(parsed) => Instructions::Program(parsed),
+        }
+    }
+}
@@ -15,36 +15,126 @@ impl Program for System {
         data: &[u8],
         accounts: &[Pubkey],
     ) -> SoleanaResult<Self::Instructions> {
-        let accs = System::match_accounts(ix_accounts_indexes, accounts);
-        match data[0..4].to_vec()[..] {
-            [0x02, 0x00, 0x00, 0x00] => {
-                let lamports = u64::from_le_bytes(data[4..12].try_into().unwrap());
+        let accs = System::match_accounts(ix_accounts_indexes, accounts)?;
+        let account = |index: usize| {
+            accs.get(index)
+                .copied()
+                .ok_or(SoleanaError::NotEnoughAccounts)
+        };
+        let mut data = DataCursor::new(data);
+        match data.read_u32()? {
+            2 => {
+                let lamports = data.read_u64()?;
                 Ok(SystemInstructions::Transfer {
                     lamports,
                     accounts: SystemTransferAccounts {
-                        from: accs[0],
-                        to: accs[1],
+                        from: account(0)?,
+                        to: account(1)?,
                     },
                 })
             }
-            _ => Err(SoleanaError::InvalidInstruction),
+            4 => Ok(SystemInstructions::AdvanceNonceAccount {
+                accounts: SystemAdvanceNonceAccountAccounts {
+                    nonce_account: account(0)?,
+                    recent_blockhashes_sysvar: account(1)?,
+                    nonce_authority: account(2)?,
+                },
+            }),
+            _ => Err(SoleanaError::UnknownInstruction),
         }
     }
 }
 
 /// Enum of all the instructions that can be given by the system program (Those who are implemented).
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SystemInstructions {
     Transfer {
         lamports: u64,
         accounts: SystemTransferAccounts,
     },
+    AdvanceNonceAccount {
+        accounts: SystemAdvanceNonceAccountAccounts,
+    },
 }
 
-impl ProgramInstructions for SystemInstructions {}
+impl ProgramInstructions for SystemInstructions {
+    fn program_name(&self) -> &str {
+        "System"
+    }
+
+    fn instruction_name(&self) -> &str {
+        match self {
+            SystemInstructions::Transfer { .. } => "Transfer",
+            SystemInstructions::AdvanceNonceAccount { .. } => "AdvanceNonceAccount",
+        }
+    }
+
+    fn accounts(&self) -> Vec<LabeledAccount> {
+        match self {
+            SystemInstructions::Transfer { accounts, .. } => vec![
+                LabeledAccount::new("from", accounts.from),
+                LabeledAccount::new("to", accounts.to),
+            ],
+            SystemInstructions::AdvanceNonceAccount { accounts } => vec![
+                LabeledAccount::new("nonce_account", accounts.nonce_account),
+                LabeledAccount::new(
+                    "recent_blockhashes_sysvar",
+                    accounts.recent_blockhashes_sysvar,
+                ),
+                LabeledAccount::new("nonce_authority", accounts.nonce_authority),
+            ],
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            SystemInstructions::Transfer { lamports, .. } => {
+                Value::Struct(vec![("lamports".to_string(), Value::U64(*lamports))])
+            }
+            SystemInstructions::AdvanceNonceAccount { .. } => Value::empty(),
+        }
+    }
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SystemTransferAccounts {
     pub from: Pubkey,
     pub to: Pubkey,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemAdvanceNonceAccountAccounts {
+    pub nonce_account: Pubkey,
+    pub recent_blockhashes_sysvar: Pubkey,
+    pub nonce_authority: Pubkey,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_accounts() {
+        let transfer = [&2u32.to_le_bytes()[..], &5000u64.to_le_bytes()].concat();
+        let accounts = [[0x0a; 32], [0x0b; 32]];
+
+        assert_eq!(
+            System::parse_instruction([0; 32], &vec![0, 1], &transfer, &accounts),
+            Ok(SystemInstructions::Transfer {
+                lamports: 5000,
+                accounts: SystemTransferAccounts {
+                    from: [0x0a; 32],
+                    to: [0x0b; 32],
+                },
+            })
+        );
+        assert_eq!(
+            System::parse_instruction([0; 32], &vec![0], &transfer, &accounts),
+            Err(SoleanaError::NotEnoughAccounts)
+        );
+        assert_eq!(
+            System::parse_instruction([0; 32], &vec![0, 2], &transfer, &accounts),
+            Err(SoleanaError::InvalidAccountIndex)
+        );
+    }
+}
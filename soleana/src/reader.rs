@@ -1,12 +1,16 @@
 use crate::{
     error::{SoleanaError, SoleanaResult},
-    types::{Hash, Header, Indicator, Pubkey, Signature, LUT},
+    limits::{Limit, ParserLimits},
+    shortvec,
+    types::{Hash, Header, Indicator, Pubkey, RawInstruction, Signature, LUT},
 };
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
+/// A cursor over the bytes of a serialized transaction, every read is checked.
 pub struct Reader<'a> {
     bytes: Cow<'a, [u8]>,
     cursor: usize,
+    limits: ParserLimits,
 }
 
 impl<'a> Reader<'a> {
@@ -16,6 +20,7 @@ impl<'a> Reader<'a> {
         Self {
             bytes: Cow::Borrowed(bytes),
             cursor: 0,
+            limits: ParserLimits::default(),
         }
     }
 
@@ -25,36 +30,111 @@ impl<'a> Reader<'a> {
         Self {
             bytes: Cow::Borrowed(&[]),
             cursor: 0,
+            limits: ParserLimits::default(),
         }
     }
 
+    /// Sets the [`ParserLimits`] enforced by the reader.
+    #[inline]
+    pub fn with_limits(mut self, limits: ParserLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Set the reader to a new buffer of bytes.
     #[inline]
-    pub(crate) fn set_bytes(&mut self, bytes: Cow<'a, [u8]>) {
-        self.bytes = bytes.into();
+    pub(crate) fn set_bytes(&mut self, bytes: Cow<'a, [u8]>) -> SoleanaResult<()> {
+        if bytes.len() > self.limits.max_input_bytes {
+            return Err(SoleanaError::LimitExceeded(Limit::InputBytes));
+        }
+
+        self.bytes = bytes;
         self.cursor = 0;
+        Ok(())
     }
 
     /// Set the reader to a new buffer of bytes from a string.
     ///
-    /// The string is expected to be a hex string.
+    /// The string is expected to be a hex string, with two digits per byte.
     #[inline]
     pub(crate) fn set_bytes_from_str(&mut self, transaction: &'a str) -> SoleanaResult<()> {
-        let bytes = (0..transaction.len())
-            .step_by(2)
-            .map(|i| {
-                u8::from_str_radix(&transaction[i..i + 2], 16)
-                    .map_err(|_| SoleanaError::InvalidHexString)
+        if transaction.len() / 2 > self.limits.max_input_bytes {
+            return Err(SoleanaError::LimitExceeded(Limit::InputBytes));
+        }
+
+        if transaction.len() % 2 != 0 {
+            return Err(SoleanaError::InvalidHexString);
+        }
+
+        let bytes = transaction
+            .as_bytes()
+            .chunks_exact(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or(SoleanaError::InvalidHexString)
             })
             .collect::<SoleanaResult<Vec<u8>>>()?;
 
-        self.set_bytes(bytes.into());
+        self.set_bytes(bytes.into())
+    }
+
+    /// Checks that `count` items of `item_size` bytes are allowed by `max` and fit in the
+    /// remaining bytes, so the caller can safely allocate for them.
+    fn check_len(
+        &self,
+        count: usize,
+        item_size: usize,
+        max: usize,
+        limit: Limit,
+    ) -> SoleanaResult<()> {
+        if count > max {
+            return Err(SoleanaError::LimitExceeded(limit));
+        }
+
+        if count.saturating_mul(item_size) > self.remaining().len() {
+            return Err(SoleanaError::NotEnoughBytes);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the position of the cursor in the buffer.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.cursor
+    }
+
+    /// Returns the bytes left to read.
+    #[inline]
+    pub fn remaining(&self) -> &[u8] {
+        self.bytes.get(self.cursor..).unwrap_or_default()
+    }
+
+    /// Moves the cursor to `position`, which can't be past the end of the buffer.
+    pub fn seek(&mut self, position: usize) -> SoleanaResult<()> {
+        if position > self.bytes.len() {
+            return Err(SoleanaError::NotEnoughBytes);
+        }
+
+        self.cursor = position;
         Ok(())
     }
 
+    /// Returns the next `count` bytes without advancing the cursor.
+    pub fn peek(&self, count: usize) -> SoleanaResult<&[u8]> {
+        self.remaining()
+            .get(..count)
+            .ok_or(SoleanaError::NotEnoughBytes)
+    }
+
     /// Reads a sequence of bytes from the buffer.
-    fn read_bytes(&mut self, count: usize) -> SoleanaResult<&[u8]> {
-        let end = self.cursor + count;
+    pub fn read_bytes(&mut self, count: usize) -> SoleanaResult<&[u8]> {
+        let end = self
+            .cursor
+            .checked_add(count)
+            .ok_or(SoleanaError::NotEnoughBytes)?;
         let slice = self
             .bytes
             .get(self.cursor..end)
@@ -64,7 +144,7 @@ impl<'a> Reader<'a> {
     }
 
     /// Reads a single byte from the buffer.
-    fn read_byte(&mut self) -> SoleanaResult<u8> {
+    pub fn read_byte(&mut self) -> SoleanaResult<u8> {
         self.read_bytes(1).map(|bytes| bytes[0])
     }
 
@@ -73,43 +153,35 @@ impl<'a> Reader<'a> {
         self.bytes.get(self.cursor).copied()
     }
 
-    /// Reads a compact u16 from the buffer.
-    fn read_compact_u16(&mut self) -> SoleanaResult<u16> {
-        let mut value: u16 = 0;
-        let mut shift: u8 = 0;
-
-        for i in 0..3 {
-            let byte = self.read_byte()?;
-
-            if i == 2 && (byte & 0x7f) != 0 {
-                return Err(SoleanaError::CompactU16Overflow);
-            }
-
-            value |= u16::from(byte & 0x7f) << shift;
-
-            if byte & 0x80 == 0 {
-                break;
-            }
-
-            shift += 7;
-        }
-
+    /// Reads a compact u16 from the buffer, see [`shortvec::decode`].
+    pub fn read_compact_u16(&mut self) -> SoleanaResult<u16> {
+        let (value, len) = shortvec::decode(self.remaining())?;
+        self.cursor += len;
         Ok(value)
     }
 
-    fn read_compact_array(&mut self) -> SoleanaResult<Vec<u8>> {
-        let len = self.read_compact_u16()?;
-        self.read_bytes(len as usize).map(|bytes| bytes.to_vec())
+    /// Reads a compact array of bytes: a compact u16 length followed by the bytes.
+    pub fn read_compact_bytes(&mut self) -> SoleanaResult<&[u8]> {
+        let len = self.read_compact_u16()? as usize;
+        self.read_bytes(len)
+    }
+
+    /// Reads a compact array of bytes from the buffer, rejecting it if it is longer than `max`.
+    fn read_compact_array(&mut self, max: usize, limit: Limit) -> SoleanaResult<Vec<u8>> {
+        let len = self.read_compact_u16()? as usize;
+        self.check_len(len, 1, max, limit)?;
+        self.read_bytes(len).map(|bytes| bytes.to_vec())
     }
 
     /// Reads the indicator from the buffer.
     ///
-    /// Either `Legacy`(0x00) or `V0`(0x80).
+    /// Either `Legacy`(no prefix) or a versioned message (`0x80 | version`).
+    /// Unknown versions are rejected with [`SoleanaError::UnsupportedVersion`].
     pub(crate) fn indicator(&mut self) -> SoleanaResult<Indicator> {
         match self.peek_byte() {
-            Some(0x80) => {
+            Some(byte) if byte & Indicator::VERSION_PREFIX != 0 => {
                 self.read_byte()?;
-                Ok(Indicator::V0)
+                Indicator::from_version(byte & !Indicator::VERSION_PREFIX)
             }
             _ => Ok(Indicator::Legacy),
         }
@@ -120,7 +192,10 @@ impl<'a> Reader<'a> {
     /// The number of signatures is read from the buffer using a compact u16.
     /// Each signature is read from the buffer using a 64-byte slice.
     pub(crate) fn read_signatures(&mut self) -> SoleanaResult<Vec<Signature>> {
-        (0..self.read_compact_u16()? as usize)
+        let count = self.read_compact_u16()? as usize;
+        self.check_len(count, 64, self.limits.max_signatures, Limit::Signatures)?;
+
+        (0..count)
             .map(|_| {
                 self.read_bytes(64)?
                     .try_into()
@@ -150,7 +225,10 @@ impl<'a> Reader<'a> {
     ///
     /// The accounts are read from the buffer using a 32-byte slice.
     pub(crate) fn read_accounts(&mut self) -> SoleanaResult<Vec<Pubkey>> {
-        (0..self.read_compact_u16()? as usize)
+        let count = self.read_compact_u16()? as usize;
+        self.check_len(count, 32, self.limits.max_accounts, Limit::Accounts)?;
+
+        (0..count)
             .map(|_| {
                 self.read_bytes(32)?
                     .try_into()
@@ -176,37 +254,83 @@ impl<'a> Reader<'a> {
     pub(crate) fn read_instructions(
         &mut self,
         accounts: &[Pubkey],
-    ) -> SoleanaResult<Vec<(Pubkey, Vec<u8>, Vec<u8>)>> {
-        (0..self.read_byte()? as usize)
+    ) -> SoleanaResult<Vec<RawInstruction>> {
+        let count = self.read_compact_u16()? as usize;
+        // Every instruction is at least 3 bytes: program id index and two empty compact arrays.
+        self.check_len(count, 3, self.limits.max_instructions, Limit::Instructions)?;
+
+        (0..count)
             .map(|_| {
-                let program_id = accounts[self.read_byte()? as usize];
-                let ix_acc = self.read_compact_array()?;
-                let data = self.read_compact_array()?;
-                Ok((program_id, ix_acc, data))
+                let program_id = *accounts
+                    .get(self.read_byte()? as usize)
+                    .ok_or(SoleanaError::InvalidAccountIndex)?;
+                let account_indexes = self.read_compact_array(usize::MAX, Limit::Accounts)?;
+                let data = self.read_compact_array(
+                    self.limits.max_instruction_data_len,
+                    Limit::InstructionData,
+                )?;
+                Ok(RawInstruction {
+                    program_id,
+                    account_indexes,
+                    data,
+                })
             })
             .collect()
     }
 
+    /// Reads the sections that follow the instructions, depending on the message version.
+    ///
+    /// Legacy messages end after the instructions, V0 messages carry the address table lookups.
+    pub(crate) fn read_versioned_sections(
+        &mut self,
+        indicator: Indicator,
+        accounts: &mut Vec<Pubkey>,
+        registered_luts: &HashMap<Pubkey, Vec<Pubkey>>,
+    ) -> SoleanaResult<Option<Vec<LUT>>> {
+        match indicator {
+            Indicator::Legacy => Ok(None),
+            Indicator::V0 => self.read_luts(accounts, registered_luts).map(Some),
+        }
+    }
+
     /// Reads the LUTs from the buffer and extends the provided accounts vector with writable and readonly accounts.
-    pub(crate) fn read_luts(&mut self, accounts: &mut Vec<Pubkey>) -> SoleanaResult<Vec<LUT>> {
+    ///
+    /// Accounts are only resolved for the luts found in `registered_luts`.
+    pub(crate) fn read_luts(
+        &mut self,
+        accounts: &mut Vec<Pubkey>,
+        registered_luts: &HashMap<Pubkey, Vec<Pubkey>>,
+    ) -> SoleanaResult<Vec<LUT>> {
         let mut writable_accounts = Vec::new();
         let mut readonly_accounts = Vec::new();
 
-        let registry = crate::registry::registry().read().unwrap();
+        let count = self.read_compact_u16()? as usize;
+        // Every lut is at least 34 bytes: account key and two empty compact arrays.
+        self.check_len(count, 34, self.limits.max_luts, Limit::Luts)?;
 
-        let luts = (0..self.read_byte()? as usize)
+        let luts = (0..count)
             .map(|_| {
                 let pk_bytes = self.read_bytes(32)?;
                 let pk: Pubkey = pk_bytes
                     .try_into()
                     .map_err(|_| SoleanaError::NotEnoughBytes)?;
 
-                let writable_indexes = self.read_compact_array()?;
-                let readonly_indexes = self.read_compact_array()?;
-
-                if let Some(lut) = registry.luts.get(&pk) {
-                    writable_accounts.extend(writable_indexes.iter().map(|&i| lut[i as usize]));
-                    readonly_accounts.extend(readonly_indexes.iter().map(|&i| lut[i as usize]));
+                let writable_indexes = self.read_compact_array(usize::MAX, Limit::Accounts)?;
+                let readonly_indexes = self.read_compact_array(usize::MAX, Limit::Accounts)?;
+
+                if let Some(lut) = registered_luts.get(&pk) {
+                    let resolve = |indexes: &[u8]| {
+                        indexes
+                            .iter()
+                            .map(|&i| {
+                                lut.get(i as usize)
+                                    .copied()
+                                    .ok_or(SoleanaError::InvalidAccountIndex)
+                            })
+                            .collect::<SoleanaResult<Vec<Pubkey>>>()
+                    };
+                    writable_accounts.extend(resolve(&writable_indexes)?);
+                    readonly_accounts.extend(resolve(&readonly_indexes)?);
                 }
 
                 Ok(LUT {
@@ -223,3 +347,128 @@ impl<'a> Reader<'a> {
         Ok(luts)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signatures_length_prefix_is_checked_before_allocating() {
+        // Claims 0x3fff signatures but only carries a single byte.
+        let mut reader = Reader::new(&[0xff, 0x7f, 0x00]).with_limits(ParserLimits::unlimited());
+        assert_eq!(reader.read_signatures(), Err(SoleanaError::NotEnoughBytes));
+
+        let mut reader = Reader::new(&[0xff, 0x7f, 0x00]);
+        assert_eq!(
+            reader.read_signatures(),
+            Err(SoleanaError::LimitExceeded(Limit::Signatures))
+        );
+    }
+
+    #[test]
+    fn test_accounts_limit() {
+        let mut bytes = vec![0x02];
+        bytes.extend_from_slice(&[0; 64]);
+
+        let limits = ParserLimits {
+            max_accounts: 1,
+            ..Default::default()
+        };
+        let mut reader = Reader::new(&bytes).with_limits(limits);
+        assert_eq!(
+            reader.read_accounts(),
+            Err(SoleanaError::LimitExceeded(Limit::Accounts))
+        );
+
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.read_accounts().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut reader = Reader::new(&[0x80, 0x01, 0x02, 0x0a, 0x0b, 0x0c]);
+
+        assert_eq!(reader.read_compact_u16(), Ok(0x80));
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.peek(2), Ok(&[0x02, 0x0a][..]));
+        assert_eq!(reader.read_compact_bytes(), Ok(&[0x0a, 0x0b][..]));
+        assert_eq!(reader.remaining(), &[0x0c]);
+        assert_eq!(reader.peek(2), Err(SoleanaError::NotEnoughBytes));
+
+        assert_eq!(reader.seek(7), Err(SoleanaError::NotEnoughBytes));
+        reader.seek(6).unwrap();
+        assert!(reader.remaining().is_empty());
+        reader.seek(0).unwrap();
+        assert_eq!(reader.read_byte(), Ok(0x80));
+    }
+
+    #[test]
+    fn test_non_canonical_compact_u16() {
+        assert_eq!(
+            Reader::new(&[0x81, 0x00]).read_compact_u16(),
+            Err(SoleanaError::CompactU16NonCanonical)
+        );
+        assert_eq!(
+            Reader::new(&[0xff, 0xff, 0x03]).read_compact_u16(),
+            Ok(u16::MAX)
+        );
+    }
+
+    #[test]
+    fn test_indicator() {
+        assert_eq!(Reader::new(&[0x01]).indicator(), Ok(Indicator::Legacy));
+        assert_eq!(Reader::new(&[0x80]).indicator(), Ok(Indicator::V0));
+        assert_eq!(
+            Reader::new(&[0x81]).indicator(),
+            Err(SoleanaError::UnsupportedVersion(1))
+        );
+    }
+
+    #[test]
+    fn test_input_bytes_limit() {
+        let limits = ParserLimits {
+            max_input_bytes: 1,
+            ..Default::default()
+        };
+        let mut reader = Reader::new_empty().with_limits(limits);
+        assert_eq!(
+            reader.set_bytes_from_str("0000"),
+            Err(SoleanaError::LimitExceeded(Limit::InputBytes))
+        );
+    }
+
+    #[test]
+    fn test_invalid_hex_string() {
+        let mut reader = Reader::new_empty();
+        assert_eq!(reader.set_bytes_from_str("0a0b"), Ok(()));
+        assert_eq!(reader.remaining(), &[0x0a, 0x0b]);
+
+        for hex in ["011", "0\u{e9}0", "0g"] {
+            assert_eq!(
+                reader.set_bytes_from_str(hex),
+                Err(SoleanaError::InvalidHexString)
+            );
+        }
+    }
+
+    #[test]
+    fn test_lut_index_out_of_bounds() {
+        // A single lut referencing the writable index 5 and the readonly index 0.
+        let mut bytes = vec![0x01];
+        bytes.extend_from_slice(&[0x0e; 32]);
+        bytes.extend_from_slice(&[0x01, 0x05, 0x01, 0x00]);
+
+        let registered_luts = HashMap::from([([0x0e; 32], vec![[0x0f; 32]])]);
+        let mut accounts = Vec::new();
+        assert_eq!(
+            Reader::new(&bytes).read_luts(&mut accounts, &registered_luts),
+            Err(SoleanaError::InvalidAccountIndex)
+        );
+
+        let registered_luts = HashMap::from([([0x0e; 32], vec![[0x0f; 32]; 6])]);
+        assert!(Reader::new(&bytes)
+            .read_luts(&mut accounts, &registered_luts)
+            .is_ok());
+        assert_eq!(accounts, vec![[0x0f; 32]; 2]);
+    }
+}
@@ -1,66 +1,844 @@
 use std::{
-    collections::HashMap,
-    sync::{OnceLock, RwLock},
+    collections::{HashMap, HashSet},
+    sync::{Arc, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
 use crate::{
-    error::SoleanaResult,
-    programs::{Program, ProgramInstructions},
-    types::{CompleteAddressLookupTable, Pubkey},
+    anchor::{self, Discriminator},
+    error::{SoleanaError, SoleanaResult},
+    events::{EmittedEvent, Event, EventDecoder, EventParser},
+    idl::{Idl, IdlParser},
+    program_errors::{self, CustomError},
+    programs::{
+        compute_budget::ComputeBudget, system::System, InstructionParser, Program,
+        ProgramInstructions, ProgramParser,
+    },
+    types::{Cluster, CompleteAddressLookupTable, Pubkey},
 };
 
-pub(crate) type ParserFn = fn(
-    program_id: Pubkey,
-    ix_accounts: &Vec<u8>,
-    data: &[u8],
-    accounts: &[Pubkey],
-) -> SoleanaResult<Box<dyn ProgramInstructions>>;
+pub(crate) type LutFetchFn = Box<dyn Fn(&[u8; 32]) -> CompleteAddressLookupTable + Send + Sync>;
 
+#[derive(Default)]
 pub(crate) struct RegistryInner {
-    pub(crate) programs: HashMap<Pubkey, ParserFn>,
+    pub(crate) programs: HashMap<Pubkey, Arc<dyn InstructionParser>>,
+    /// The names of the instructions of every program, keyed by discriminator.
+    pub(crate) instruction_names: HashMap<Pubkey, HashMap<Discriminator, String>>,
+    /// The event decoders of every program, keyed by event discriminator.
+    pub(crate) event_decoders: HashMap<Pubkey, HashMap<Discriminator, Arc<dyn EventDecoder>>>,
+    /// The custom errors of every program, keyed by code.
+    pub(crate) errors: HashMap<Pubkey, HashMap<u32, CustomError>>,
+    /// The programs built with Anchor, they raise the framework errors of Anchor.
+    pub(crate) anchor_programs: HashSet<Pubkey>,
     /// We store the luts as a map of account's, trying to keep the same order as the original lut.
     /// Since the transaction returns the index of the account.
     pub(crate) luts: HashMap<Pubkey, Vec<Pubkey>>,
-    pub(crate) lut_fetch_fn:
-        Option<Box<dyn Fn(&[u8; 32]) -> CompleteAddressLookupTable + Send + Sync>>,
+    pub(crate) lut_fetch_fn: Option<LutFetchFn>,
+    /// The cluster used to select the ids of the registered programs.
+    pub(crate) cluster: Cluster,
 }
 
-static REGISTRY: OnceLock<RwLock<RegistryInner>> = OnceLock::new();
+impl RegistryInner {
+    /// Register's a program to the registry.
+    fn register_program<P: Program + 'static>(&mut self)
+    where
+        P::Instructions: ProgramInstructions + 'static,
+    {
+        let ids = P::program_ids(&self.cluster);
+        self.register_program_with_ids::<P>(&ids);
+    }
+
+    /// Register's a program to the registry under every id of `ids`.
+    fn register_program_with_ids<P: Program + 'static>(&mut self, ids: &[Pubkey])
+    where
+        P::Instructions: ProgramInstructions + 'static,
+    {
+        let parser: Arc<dyn InstructionParser> = Arc::new(ProgramParser::<P>::new());
+        for &id in ids {
+            self.register_parser(id, parser.clone());
+        }
+    }
+
+    /// Register's a stateful parser to the registry.
+    fn register_parser(&mut self, program_id: Pubkey, parser: Arc<dyn InstructionParser>) {
+        self.programs.insert(program_id, parser);
+    }
+
+    /// Register's the built-in programs, without overriding programs registered under their ids.
+    fn register_builtins(&mut self) {
+        let builtins: [(Vec<Pubkey>, Arc<dyn InstructionParser>); 2] = [
+            (
+                System::program_ids(&self.cluster),
+                Arc::new(ProgramParser::<System>::new()),
+            ),
+            (
+                ComputeBudget::program_ids(&self.cluster),
+                Arc::new(ProgramParser::<ComputeBudget>::new()),
+            ),
+        ];
+
+        for (ids, parser) in builtins {
+            for id in ids {
+                self.programs.entry(id).or_insert_with(|| parser.clone());
+            }
+        }
+    }
+
+    /// Names the instructions of `program_id` prefixed with `discriminator`.
+    fn register_instruction_name(
+        &mut self,
+        program_id: Pubkey,
+        discriminator: Discriminator,
+        name: String,
+    ) {
+        self.instruction_names
+            .entry(program_id)
+            .or_default()
+            .insert(discriminator, name);
+    }
+
+    fn register_anchor_instructions(&mut self, program_id: Pubkey, names: &[&str]) {
+        self.register_anchor_program(program_id);
+        for name in names {
+            self.register_instruction_name(
+                program_id,
+                anchor::anchor_discriminator(name),
+                name.to_string(),
+            );
+        }
+    }
+
+    /// Registers the decoder of the events of `program_id` prefixed by `discriminator`.
+    fn register_event_decoder(
+        &mut self,
+        program_id: Pubkey,
+        discriminator: Discriminator,
+        decoder: Arc<dyn EventDecoder>,
+    ) {
+        self.event_decoders
+            .entry(program_id)
+            .or_default()
+            .insert(discriminator, decoder);
+    }
+
+    fn register_error(&mut self, program_id: Pubkey, error: CustomError) {
+        self.errors
+            .entry(program_id)
+            .or_default()
+            .insert(error.code, error);
+    }
+
+    /// Marks `program_id` as built with Anchor.
+    fn register_anchor_program(&mut self, program_id: Pubkey) {
+        self.anchor_programs.insert(program_id);
+    }
 
-pub(crate) fn registry() -> &'static RwLock<RegistryInner> {
-    REGISTRY.get_or_init(|| {
-        RwLock::new(RegistryInner {
-            programs: HashMap::new(),
-            luts: HashMap::new(),
-            lut_fetch_fn: None,
+    /// Resolves the custom error `code` of `program_id`.
+    ///
+    /// The registered errors come first, then the built-in tables of the System and Token
+    /// programs, then the framework codes for the programs built with Anchor.
+    pub(crate) fn resolve_error(&self, program_id: &Pubkey, code: u32) -> Option<CustomError> {
+        if let Some(error) = self
+            .errors
+            .get(program_id)
+            .and_then(|errors| errors.get(&code))
+        {
+            return Some(error.clone());
+        }
+
+        program_errors::builtin_error(program_id, code).or_else(|| {
+            self.anchor_programs
+                .contains(program_id)
+                .then(|| program_errors::anchor_error(code))
+                .flatten()
         })
-    })
+    }
+
+    /// Registers the parser, the event decoders and the errors of `idl` under `program_id`.
+    fn register_idl_with_id(&mut self, mut idl: Idl, program_id: Pubkey) {
+        idl.address = Some(program_id);
+        if idl.anchor {
+            self.register_anchor_program(program_id);
+        }
+        for error in &idl.errors {
+            self.register_error(
+                program_id,
+                CustomError {
+                    code: error.code,
+                    name: error.name.clone(),
+                    message: error.msg.clone(),
+                },
+            );
+        }
+        let discriminators: Vec<Discriminator> =
+            idl.events.iter().map(|event| event.discriminator).collect();
+
+        let parser = Arc::new(IdlParser::new(idl));
+        for discriminator in discriminators {
+            self.register_event_decoder(program_id, discriminator, parser.clone());
+        }
+        self.register_parser(program_id, parser);
+    }
+
+    /// Returns the name registered for the discriminator prefixing `data`.
+    pub(crate) fn instruction_name(&self, program_id: &Pubkey, data: &[u8]) -> Option<&str> {
+        self.instruction_names
+            .get(program_id)?
+            .get(&anchor::discriminator_of(data)?)
+            .map(String::as_str)
+    }
+
+    /// Register's a lut to the registry.
+    fn register_lut(&mut self, lut: CompleteAddressLookupTable) {
+        self.luts.insert(lut.account_key, lut.accounts);
+    }
+
+    /// Register's a lut fetch function to the registry.
+    fn register_lut_fetch_fn<F, R>(&mut self, fetch_fn: F)
+    where
+        F: Fn(&[u8; 32]) -> R + Send + Sync + 'static,
+        R: Into<CompleteAddressLookupTable>,
+    {
+        self.lut_fetch_fn = Some(Box::new(move |key| fetch_fn(key).into()));
+    }
 }
 
-/// Register's a program to the registry.
-pub(crate) fn register_program<P: Program>()
-where
-    P::Instructions: ProgramInstructions + 'static,
-{
-    fn wrapper<P: Program>(
+/// [`Registry`] holds the programs and luts used by a [`TransactionsParser`](crate::TransactionsParser).
+///
+/// A registry is built with a [`RegistryBuilder`] and shared between parsers with an [`Arc`],
+/// so parsers with different programs or luts can coexist in the same process.
+pub struct Registry {
+    inner: RwLock<RegistryInner>,
+}
+
+static GLOBAL_REGISTRY: OnceLock<Arc<Registry>> = OnceLock::new();
+
+impl Registry {
+    /// Creates a new [`RegistryBuilder`].
+    pub fn builder() -> RegistryBuilder {
+        RegistryBuilder::new()
+    }
+
+    /// Returns the process-wide default registry, used by [`TransactionsParser::new`](crate::TransactionsParser::new).
+    ///
+    /// Registering a program or a lut in it affects every parser that uses it.
+    pub fn global() -> Arc<Registry> {
+        GLOBAL_REGISTRY
+            .get_or_init(|| RegistryBuilder::new().build())
+            .clone()
+    }
+
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, RegistryInner> {
+        self.inner.read().unwrap()
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, RegistryInner> {
+        self.inner.write().unwrap()
+    }
+
+    /// Registers a program to the registry.
+    pub fn register_program<P: Program + 'static>(&self)
+    where
+        P::Instructions: ProgramInstructions + 'static,
+    {
+        self.write().register_program::<P>();
+    }
+
+    /// Registers a program to the registry under every id of `ids`, instead of its own ids.
+    ///
+    /// Useful for programs sharing a layout across deployments, forks or versions.
+    pub fn register_program_with_ids<P: Program + 'static>(&self, ids: &[Pubkey])
+    where
+        P::Instructions: ProgramInstructions + 'static,
+    {
+        self.write().register_program_with_ids::<P>(ids);
+    }
+
+    /// Replaces the parsers registered under the ids of `P` with `P`.
+    ///
+    /// Returns `true` if a parser was registered under one of the ids before.
+    pub fn replace_program<P: Program + 'static>(&self) -> bool
+    where
+        P::Instructions: ProgramInstructions + 'static,
+    {
+        let mut registry = self.write();
+        let ids = P::program_ids(&registry.cluster);
+        let replaced = ids.iter().any(|id| registry.programs.contains_key(id));
+        registry.register_program_with_ids::<P>(&ids);
+        replaced
+    }
+
+    /// Removes the parser registered under `program_id`.
+    ///
+    /// Returns `true` if a parser was registered under `program_id`.
+    pub fn unregister_program(&self, program_id: &Pubkey) -> bool {
+        self.write().programs.remove(program_id).is_some()
+    }
+
+    /// Returns the id and the name of every registered program, sorted by id.
+    ///
+    /// The name is `None` for the parsers that don't know it, such as closures.
+    pub fn registered_programs(&self) -> Vec<(Pubkey, Option<String>)> {
+        let mut programs: Vec<(Pubkey, Option<String>)> = self
+            .read()
+            .programs
+            .iter()
+            .map(|(id, parser)| (*id, parser.name().map(str::to_string)))
+            .collect();
+        programs.sort_unstable_by_key(|(id, _)| *id);
+        programs
+    }
+
+    /// Returns the cluster used to select the ids of the registered programs.
+    pub fn cluster(&self) -> Cluster {
+        self.read().cluster.clone()
+    }
+
+    /// Registers a stateful parser to the registry under `program_id`.
+    pub fn register_parser(&self, program_id: Pubkey, parser: Arc<dyn InstructionParser>) {
+        self.write().register_parser(program_id, parser);
+    }
+
+    /// Registers a closure as the parser of `program_id`.
+    pub fn register_parser_fn<F>(&self, program_id: Pubkey, parser: F)
+    where
+        F: Fn(Pubkey, &Vec<u8>, &[u8], &[Pubkey]) -> SoleanaResult<Box<dyn ProgramInstructions>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.register_parser(program_id, Arc::new(parser));
+    }
+
+    /// Registers the parser of `idl` under the IDL address, returning the address.
+    pub fn register_idl(&self, idl: Idl) -> SoleanaResult<Pubkey> {
+        let program_id = idl
+            .address
+            .ok_or_else(|| SoleanaError::InvalidIdl("missing `address`".to_string()))?;
+        self.register_idl_with_id(idl, program_id);
+        Ok(program_id)
+    }
+
+    /// Registers the parser of `idl` under `program_id`, whatever the IDL address is.
+    ///
+    /// The events and the errors of the IDL are registered too.
+    pub fn register_idl_with_id(&self, idl: Idl, program_id: Pubkey) {
+        self.write().register_idl_with_id(idl, program_id);
+    }
+
+    /// Names the instructions of `program_id` whose data starts with `discriminator`.
+    ///
+    /// Used to name the instructions of programs without a registered parser.
+    pub fn register_instruction_name<S: Into<String>>(
+        &self,
         program_id: Pubkey,
-        ix_accounts: &Vec<u8>,
-        data: &[u8],
-        accounts: &[Pubkey],
-    ) -> SoleanaResult<Box<dyn ProgramInstructions>>
+        discriminator: Discriminator,
+        name: S,
+    ) {
+        self.write()
+            .register_instruction_name(program_id, discriminator, name.into());
+    }
+
+    /// Names the instructions of the Anchor program `program_id` from their handler names.
+    pub fn register_anchor_instructions(&self, program_id: Pubkey, names: &[&str]) {
+        self.write().register_anchor_instructions(program_id, names);
+    }
+
+    /// Returns the name registered for the discriminator prefixing `data`.
+    pub fn instruction_name(&self, program_id: &Pubkey, data: &[u8]) -> Option<String> {
+        self.read()
+            .instruction_name(program_id, data)
+            .map(str::to_string)
+    }
+
+    /// Registers the hand-written event `E` of `program_id`.
+    pub fn register_event<E: Event + 'static>(&self, program_id: Pubkey) {
+        self.register_event_decoder(
+            program_id,
+            E::DISCRIMINATOR,
+            Arc::new(EventParser::<E>::new()),
+        );
+    }
+
+    /// Registers the decoder of the events of `program_id` prefixed by `discriminator`.
+    pub fn register_event_decoder(
+        &self,
+        program_id: Pubkey,
+        discriminator: Discriminator,
+        decoder: Arc<dyn EventDecoder>,
+    ) {
+        self.write()
+            .register_event_decoder(program_id, discriminator, decoder);
+    }
+
+    /// Extracts the events of the `Program data:` lines of `logs`, each one attributed to the
+    /// program executing when it was logged.
+    pub fn log_events<S: AsRef<str>>(&self, logs: &[S]) -> SoleanaResult<Vec<EmittedEvent>> {
+        self.read().log_events(logs)
+    }
+
+    /// Extracts the event of an `emit_cpi!` instruction of `program_id`, `None` if the
+    /// instruction isn't one.
+    pub fn cpi_event(&self, program_id: Pubkey, data: &[u8]) -> Option<EmittedEvent> {
+        self.read().cpi_event(program_id, data)
+    }
+
+    /// Registers the custom error `error` of `program_id`, replacing a built-in one.
+    pub fn register_error(&self, program_id: Pubkey, error: CustomError) {
+        self.write().register_error(program_id, error);
+    }
+
+    /// Marks `program_id` as built with Anchor, so its errors include the framework codes.
+    ///
+    /// Programs registered from an Anchor IDL or with Anchor instruction names are marked
+    /// already.
+    pub fn register_anchor_program(&self, program_id: Pubkey) {
+        self.write().register_anchor_program(program_id);
+    }
+
+    /// Resolves the custom error `code` raised by `program_id`, the `n` of
+    /// `InstructionError::Custom(n)`.
+    ///
+    /// The registered errors come first, then the built-in tables of the System, Token and
+    /// Token-2022 programs. The framework codes of Anchor are only resolved for the programs
+    /// built with Anchor.
+    pub fn resolve_error(&self, program_id: &Pubkey, code: u32) -> Option<CustomError> {
+        self.read().resolve_error(program_id, code)
+    }
+
+    /// Registers a lut to the registry.
+    pub fn register_lut<T: Into<CompleteAddressLookupTable>>(&self, lut: T) {
+        self.write().register_lut(lut.into());
+    }
+
+    /// Registers a lut fetch function to the registry.
+    pub fn register_lut_fetch_fn<F, R>(&self, fetch_fn: F)
+    where
+        F: Fn(&[u8; 32]) -> R + Send + Sync + 'static,
+        R: Into<CompleteAddressLookupTable>,
+    {
+        self.write().register_lut_fetch_fn(fetch_fn);
+    }
+
+    /// Fetches a lut from the fetch function and registers it to the registry.
+    pub fn fetch_and_register_lut(&self, lut_account: Pubkey) -> SoleanaResult<()> {
+        let lut = {
+            let registry = self.read();
+            let fetch_fn = registry
+                .lut_fetch_fn
+                .as_ref()
+                .ok_or(SoleanaError::NoLutFetchFnRegistered)?;
+
+            (fetch_fn)(&lut_account)
+        };
+
+        self.register_lut(lut);
+
+        Ok(())
+    }
+}
+
+/// [`RegistryBuilder`] builds a [`Registry`].
+///
+/// The built-in programs (System and Compute Budget) are registered by default, programs
+/// registered under the same ids take precedence over them.
+pub struct RegistryBuilder {
+    inner: RegistryInner,
+    builtins: bool,
+}
+
+impl RegistryBuilder {
+    /// Creates a new [`RegistryBuilder`] for mainnet, with the built-in programs.
+    pub fn new() -> Self {
+        Self::with_cluster(Cluster::Mainnet)
+    }
+
+    /// Creates a new [`RegistryBuilder`] for `cluster`, with the built-in programs.
+    ///
+    /// Every program registered in the registry uses its ids on `cluster`.
+    pub fn with_cluster(cluster: Cluster) -> Self {
+        Self {
+            inner: RegistryInner {
+                cluster,
+                ..Default::default()
+            },
+            builtins: true,
+        }
+    }
+
+    /// Doesn't register the built-in programs.
+    pub fn without_builtins(mut self) -> Self {
+        self.builtins = false;
+        self
+    }
+
+    /// Registers a program to the registry.
+    pub fn program<P: Program + 'static>(mut self) -> Self
+    where
+        P::Instructions: ProgramInstructions + 'static,
+    {
+        self.inner.register_program::<P>();
+        self
+    }
+
+    /// Registers a program to the registry under every id of `ids`, instead of its own ids.
+    pub fn program_with_ids<P: Program + 'static>(mut self, ids: &[Pubkey]) -> Self
     where
         P::Instructions: ProgramInstructions + 'static,
     {
-        let instruction = P::parse_instruction(program_id, ix_accounts, data, accounts)?;
-        Ok(Box::new(instruction))
+        self.inner.register_program_with_ids::<P>(ids);
+        self
+    }
+
+    /// Registers a stateful parser to the registry under `program_id`.
+    pub fn parser(mut self, program_id: Pubkey, parser: Arc<dyn InstructionParser>) -> Self {
+        self.inner.register_parser(program_id, parser);
+        self
+    }
+
+    /// Registers a closure as the parser of `program_id`.
+    pub fn parser_fn<F>(self, program_id: Pubkey, parser: F) -> Self
+    where
+        F: Fn(Pubkey, &Vec<u8>, &[u8], &[Pubkey]) -> SoleanaResult<Box<dyn ProgramInstructions>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.parser(program_id, Arc::new(parser))
+    }
+
+    /// Names the instructions of `program_id` whose data starts with `discriminator`.
+    pub fn instruction_name<S: Into<String>>(
+        mut self,
+        program_id: Pubkey,
+        discriminator: Discriminator,
+        name: S,
+    ) -> Self {
+        self.inner
+            .register_instruction_name(program_id, discriminator, name.into());
+        self
+    }
+
+    /// Names the instructions of the Anchor program `program_id` from their handler names.
+    pub fn anchor_instructions(mut self, program_id: Pubkey, names: &[&str]) -> Self {
+        self.inner.register_anchor_instructions(program_id, names);
+        self
+    }
+
+    /// Registers the hand-written event `E` of `program_id`.
+    pub fn event<E: Event + 'static>(self, program_id: Pubkey) -> Self {
+        self.event_decoder(
+            program_id,
+            E::DISCRIMINATOR,
+            Arc::new(EventParser::<E>::new()),
+        )
+    }
+
+    /// Registers the decoder of the events of `program_id` prefixed by `discriminator`.
+    pub fn event_decoder(
+        mut self,
+        program_id: Pubkey,
+        discriminator: Discriminator,
+        decoder: Arc<dyn EventDecoder>,
+    ) -> Self {
+        self.inner
+            .register_event_decoder(program_id, discriminator, decoder);
+        self
+    }
+
+    /// Marks `program_id` as built with Anchor, so its errors include the framework codes.
+    pub fn anchor_program(mut self, program_id: Pubkey) -> Self {
+        self.inner.register_anchor_program(program_id);
+        self
+    }
+
+    /// Registers the custom error `error` of `program_id`, replacing a built-in one.
+    pub fn error(mut self, program_id: Pubkey, error: CustomError) -> Self {
+        self.inner.register_error(program_id, error);
+        self
+    }
+
+    /// Registers a lut to the registry.
+    pub fn lut<T: Into<CompleteAddressLookupTable>>(mut self, lut: T) -> Self {
+        self.inner.register_lut(lut.into());
+        self
+    }
+
+    /// Registers a lut fetch function to the registry.
+    pub fn lut_fetch_fn<F, R>(mut self, fetch_fn: F) -> Self
+    where
+        F: Fn(&[u8; 32]) -> R + Send + Sync + 'static,
+        R: Into<CompleteAddressLookupTable>,
+    {
+        self.inner.register_lut_fetch_fn(fetch_fn);
+        self
     }
 
-    let mut registry = registry().write().unwrap();
-    registry.programs.insert(P::program_id(), wrapper::<P>);
+    /// Builds the [`Registry`].
+    pub fn build(mut self) -> Arc<Registry> {
+        if self.builtins {
+            self.inner.register_builtins();
+        }
+
+        Arc::new(Registry {
+            inner: RwLock::new(self.inner),
+        })
+    }
 }
 
-/// Register's a lut to the registry.
-pub(crate) fn register_lut(lut: CompleteAddressLookupTable) {
-    let mut registry = registry().write().unwrap();
-    registry.luts.insert(lut.account_key, lut.accounts);
+impl Default for RegistryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{programs::context::ParseContext, TransactionsParser};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Amount {
+        amount: u64,
+        decimals: u8,
+    }
+
+    impl ProgramInstructions for Amount {}
+
+    /// Parses a single u64 amount, along with the decimals of the configured mint.
+    struct AmountParser {
+        decimals: HashMap<Pubkey, u8>,
+    }
+
+    impl InstructionParser for AmountParser {
+        fn parse_with_context(
+            &self,
+            context: &ParseContext,
+        ) -> SoleanaResult<Box<dyn ProgramInstructions>> {
+            let mint = context.account(0).ok_or(SoleanaError::NotEnoughAccounts)?;
+            let data = context.data();
+            let decimals = *self
+                .decimals
+                .get(&mint)
+                .ok_or(SoleanaError::InvalidInstruction)?;
+            let amount = u64::from_le_bytes(
+                data.try_into()
+                    .map_err(|_| SoleanaError::InvalidInstruction)?,
+            );
+
+            Ok(Box::new(Amount { amount, decimals }))
+        }
+    }
+
+    /// A transaction with a single instruction, carrying a 1000 amount for the `[0x0b; 32]` mint.
+    fn transaction() -> String {
+        format!(
+            "01{}{}{}{}{}",
+            "00".repeat(64),
+            "01000103",
+            "0a".repeat(32) + &"0b".repeat(32) + &"0c".repeat(32),
+            "00".repeat(32),
+            "0102010108e803000000000000",
+        )
+    }
+
+    #[test]
+    fn test_stateful_parser() {
+        let registry = Registry::builder()
+            .parser(
+                [0x0c; 32],
+                Arc::new(AmountParser {
+                    decimals: HashMap::from([([0x0b; 32], 3)]),
+                }),
+            )
+            .build();
+
+        let parser = TransactionsParser::with_registry(registry);
+        let transaction = parser.parse_transaction(&transaction()).unwrap();
+        let parsed = transaction.message.instructions[0].parsed.as_ref().unwrap();
+        assert_eq!(
+            parsed.downcast_ref(),
+            Some(&Amount {
+                amount: 1000,
+                decimals: 3
+            })
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Deposit;
+
+    impl ProgramInstructions for Deposit {}
+
+    /// A program deployed at `[0x0c; 32]` on mainnet and `[0x0d; 32]` everywhere else.
+    struct Vault;
+
+    impl Program for Vault {
+        fn program_id() -> Pubkey {
+            [0x0c; 32]
+        }
+
+        fn program_ids(cluster: &Cluster) -> Vec<Pubkey> {
+            match cluster {
+                Cluster::Mainnet => vec![[0x0c; 32]],
+                _ => vec![[0x0d; 32]],
+            }
+        }
+
+        type Instructions = Deposit;
+
+        fn parse_instruction(
+            _: Pubkey,
+            _: &Vec<u8>,
+            _: &[u8],
+            _: &[Pubkey],
+        ) -> SoleanaResult<Self::Instructions> {
+            Ok(Deposit)
+        }
+    }
+
+    #[test]
+    fn test_cluster_program_ids() {
+        let mainnet = Registry::builder().program::<Vault>().build();
+        let devnet = RegistryBuilder::with_cluster(Cluster::Devnet)
+            .program::<Vault>()
+            .build();
+
+        assert!(mainnet.read().programs.contains_key(&[0x0c; 32]));
+        assert!(!mainnet.read().programs.contains_key(&[0x0d; 32]));
+        assert!(devnet.read().programs.contains_key(&[0x0d; 32]));
+        assert!(!devnet.read().programs.contains_key(&[0x0c; 32]));
+        assert!(devnet.read().programs.contains_key(&[0; 32]));
+    }
+
+    #[test]
+    fn test_program_with_ids() {
+        let registry = Registry::builder()
+            .program_with_ids::<Vault>(&[[0x0c; 32], [0x0e; 32]])
+            .build();
+
+        let programs = &registry.read().programs;
+        assert!(Arc::ptr_eq(&programs[&[0x0c; 32]], &programs[&[0x0e; 32]]));
+    }
+
+    #[test]
+    fn test_unregister_and_replace_programs() {
+        let registry = Registry::builder().build();
+        assert_eq!(
+            registry.registered_programs(),
+            vec![
+                ([0; 32], Some("System".to_string())),
+                (
+                    ComputeBudget::program_id(),
+                    Some("ComputeBudget".to_string())
+                ),
+            ]
+        );
+
+        assert!(registry.unregister_program(&ComputeBudget::program_id()));
+        assert!(!registry.unregister_program(&ComputeBudget::program_id()));
+        assert!(!registry.replace_program::<Vault>());
+        assert!(registry.replace_program::<Vault>());
+        assert_eq!(
+            registry.registered_programs(),
+            vec![
+                ([0; 32], Some("System".to_string())),
+                ([0x0c; 32], Some("Vault".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builtins_do_not_override_programs() {
+        let registry = Registry::builder()
+            .program_with_ids::<Vault>(&[[0; 32]])
+            .build();
+        assert_eq!(
+            registry.registered_programs()[0],
+            ([0; 32], Some("Vault".to_string()))
+        );
+
+        let registry = Registry::builder().without_builtins().build();
+        assert!(registry.registered_programs().is_empty());
+    }
+
+    #[test]
+    fn test_closure_parser() {
+        let registry = Registry::builder().build();
+        registry.register_parser_fn([0x0c; 32], |_, _, data, _| {
+            Ok(Box::new(Amount {
+                amount: data.len() as u64,
+                decimals: 0,
+            }))
+        });
+
+        // Closures don't know the name of their program.
+        assert_eq!(
+            registry.registered_programs().last(),
+            Some(&([0x0c; 32], None))
+        );
+
+        let parser = TransactionsParser::with_registry(registry);
+        let transaction = parser.parse_transaction(&transaction()).unwrap();
+        let parsed = transaction.message.instructions[0].parsed.as_ref().unwrap();
+        assert_eq!(
+            parsed.downcast_ref(),
+            Some(&Amount {
+                amount: 8,
+                decimals: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_instruction_names() {
+        let deposit: String = anchor::anchor_discriminator("deposit")
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let transaction = format!(
+            "01{}{}{}{}02{}{}",
+            "00".repeat(64),
+            "01000103",
+            "0a".repeat(32) + &"0b".repeat(32) + &"0c".repeat(32),
+            "00".repeat(32),
+            "02010108".to_owned() + &deposit,
+            "0201010cdeadbeefdeadbeefdeadbeef",
+        );
+
+        let registry = Registry::builder()
+            .anchor_instructions([0x0c; 32], &["deposit", "withdraw"])
+            .build();
+        let parser = TransactionsParser::with_registry(registry.clone());
+
+        let parsed = parser.parse_transaction(&transaction).unwrap();
+        let names: Vec<_> = parsed
+            .message
+            .instructions
+            .iter()
+            .map(|instruction| instruction.name.as_deref())
+            .collect();
+        assert_eq!(names, [Some("deposit"), None]);
+
+        // The placeholder name of a parsed instruction isn't exposed, the registered name is
+        // used instead.
+        registry.register_parser_fn([0x0c; 32], |_, _, _, _| {
+            Ok(Box::new(Amount {
+                amount: 0,
+                decimals: 0,
+            }))
+        });
+        let parsed = parser.parse_transaction(&transaction).unwrap();
+        let names: Vec<_> = parsed
+            .message
+            .instructions
+            .iter()
+            .map(|instruction| instruction.name.as_deref())
+            .collect();
+        assert_eq!(names, [Some("deposit"), None]);
+        assert!(parsed.message.instructions[1].parsed.is_some());
+        assert_eq!(
+            registry.instruction_name(&[0x0c; 32], &anchor::anchor_discriminator("withdraw")),
+            Some("withdraw".to_string())
+        );
+    }
 }
@@ -0,0 +1,698 @@
+use crate::{
+    base58, base64,
+    error::{SoleanaError, SoleanaResult},
+    json::Json,
+    programs::context::ParseContext,
+    reader::Reader,
+    registry::RegistryInner,
+    types::{Instruction, Message, Pubkey, RawInstruction, Transaction, LUT},
+    TransactionsParser,
+};
+
+/// A transaction returned by the `getTransaction` RPC method.
+///
+/// Equivalent to the `EncodedConfirmedTransactionWithStatusMeta` from the solana-transaction-status. <https://docs.rs/solana-transaction-status/latest/solana_transaction_status/struct.EncodedConfirmedTransactionWithStatusMeta.html>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcTransaction {
+    pub slot: u64,
+    /// The estimated production time of the block, as a unix timestamp.
+    pub block_time: Option<i64>,
+    pub transaction: Transaction,
+    pub meta: Option<TransactionMeta>,
+}
+
+/// Equivalent to the `UiTransactionStatusMeta` from the solana-transaction-status. <https://docs.rs/solana-transaction-status/latest/solana_transaction_status/struct.UiTransactionStatusMeta.html>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionMeta {
+    pub err: Option<TransactionError>,
+    pub fee: u64,
+    pub pre_balances: Vec<u64>,
+    pub post_balances: Vec<u64>,
+    pub inner_instructions: Vec<InnerInstructions>,
+    /// The logs of the transaction, `None` if the node didn't record them.
+    pub log_messages: Option<Vec<String>>,
+    pub pre_token_balances: Vec<TokenBalance>,
+    pub post_token_balances: Vec<TokenBalance>,
+    /// The accounts loaded from the luts, `None` if the node didn't record them.
+    pub loaded_addresses: Option<LoadedAddresses>,
+    pub compute_units_consumed: Option<u64>,
+}
+
+/// Equivalent to the `TransactionError` from the solana-transaction-error, only the instruction
+/// errors are kept structured. <https://docs.rs/solana-transaction-error/latest/solana_transaction_error/enum.TransactionError.html>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TransactionError {
+    /// The error of the instruction at this index.
+    InstructionError(u8, InstructionError),
+    /// Any other error, by name.
+    Other(String),
+}
+
+/// Equivalent to the `InstructionError` from the solana-instruction, only the custom program
+/// errors are kept structured. <https://docs.rs/solana-instruction/latest/solana_instruction/error/enum.InstructionError.html>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InstructionError {
+    /// A custom program error, see [`Registry::resolve_error`](crate::registry::Registry::resolve_error).
+    Custom(u32),
+    /// Any other error, by name.
+    Other(String),
+}
+
+/// Equivalent to the `UiInnerInstructions` from the solana-transaction-status. <https://docs.rs/solana-transaction-status/latest/solana_transaction_status/struct.UiInnerInstructions.html>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InnerInstructions {
+    /// The index of the top-level instruction that invoked these instructions.
+    pub index: u8,
+    pub instructions: Vec<InnerInstruction>,
+}
+
+/// An instruction invoked through a CPI.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InnerInstruction {
+    /// The program id is resolved from the account keys and the loaded addresses.
+    pub instruction: RawInstruction,
+    /// The invocation depth, see [`Instruction::stack_height`].
+    pub stack_height: Option<u32>,
+}
+
+/// Equivalent to the `UiTransactionTokenBalance` from the solana-transaction-status. <https://docs.rs/solana-transaction-status/latest/solana_transaction_status/struct.UiTransactionTokenBalance.html>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenBalance {
+    /// The index of the token account into the accounts of the message.
+    pub account_index: u8,
+    pub mint: Pubkey,
+    pub owner: Option<Pubkey>,
+    pub program_id: Option<Pubkey>,
+    /// The raw amount, in base units of the mint.
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// The accounts loaded from the luts of a V0 transaction.
+///
+/// Equivalent to the `UiLoadedAddresses` from the solana-transaction-status. <https://docs.rs/solana-transaction-status/latest/solana_transaction_status/struct.UiLoadedAddresses.html>
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LoadedAddresses {
+    pub writable: Vec<Pubkey>,
+    pub readonly: Vec<Pubkey>,
+}
+
+impl LoadedAddresses {
+    /// Extends `accounts` with the loaded addresses, writable first like the runtime does.
+    ///
+    /// The addresses must match the indexes of `luts` one to one.
+    pub(crate) fn resolve(
+        &self,
+        luts: Option<&[LUT]>,
+        accounts: &mut Vec<Pubkey>,
+    ) -> SoleanaResult<()> {
+        let luts = luts.unwrap_or_default();
+        let writable: usize = luts.iter().map(|lut| lut.writable_indexes.len()).sum();
+        let readonly: usize = luts.iter().map(|lut| lut.readonly_indexes.len()).sum();
+
+        if writable != self.writable.len() || readonly != self.readonly.len() {
+            return Err(SoleanaError::InvalidAccountIndex);
+        }
+
+        accounts.extend(&self.writable);
+        accounts.extend(&self.readonly);
+        Ok(())
+    }
+}
+
+/// Parses a `getTransaction` response with the registry of `parser`.
+pub(crate) fn parse_rpc_transaction(
+    parser: &TransactionsParser,
+    json: &str,
+) -> SoleanaResult<RpcTransaction> {
+    let json = Json::parse(json)?;
+    // The full JSON-RPC envelope or only its result.
+    let result = json.get("result").unwrap_or(&json);
+
+    let meta = optional(result, "meta");
+    let loaded_addresses = meta
+        .and_then(|meta| optional(meta, "loadedAddresses"))
+        .map(|loaded| {
+            Ok(LoadedAddresses {
+                writable: pubkeys(field(loaded, "writable")?)?,
+                readonly: pubkeys(field(loaded, "readonly")?)?,
+            })
+        })
+        .transpose()?;
+
+    // Without the loaded addresses, the accounts of the luts come from the registered luts.
+    let bytes = transaction_bytes(field(result, "transaction")?)?;
+    let mut reader = Reader::new_empty().with_limits(parser.limits);
+    reader.set_bytes(bytes.into())?;
+    let mut transaction = parser.read_transaction(&mut reader, loaded_addresses.as_ref())?;
+
+    let loaded = match &loaded_addresses {
+        Some(loaded) => loaded.clone(),
+        None => registered_addresses(&parser.registry.read(), transaction.message.luts.as_deref()),
+    };
+    let accounts = [
+        &transaction.message.account_keys[..],
+        &loaded.writable,
+        &loaded.readonly,
+    ]
+    .concat();
+    let meta = meta
+        .map(|meta| self::meta(meta, &accounts, loaded_addresses))
+        .transpose()?;
+    if let Some(meta) = &meta {
+        parser.attach_inner_instructions(
+            &mut transaction.message,
+            &accounts,
+            &meta.inner_instructions,
+        )?;
+    }
+
+    Ok(RpcTransaction {
+        slot: u64_field(result, "slot")?,
+        block_time: optional(result, "blockTime")
+            .map(|time| time.as_i64().ok_or(SoleanaError::InvalidJson))
+            .transpose()?,
+        transaction,
+        meta,
+    })
+}
+
+impl TransactionsParser {
+    /// Parses the inner instructions with the registered programs and attaches them to the
+    /// top-level instruction that invoked them.
+    ///
+    /// `accounts` are the accounts of the message followed by the loaded addresses.
+    fn attach_inner_instructions(
+        &self,
+        message: &mut Message,
+        accounts: &[Pubkey],
+        inner_instructions: &[InnerInstructions],
+    ) -> SoleanaResult<()> {
+        let instructions = message
+            .instructions
+            .iter()
+            .map(|instruction| RawInstruction {
+                program_id: instruction.program_id,
+                account_indexes: instruction.account_indexes.clone(),
+                data: instruction.raw.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let registry = self.registry.read();
+        for inner in inner_instructions {
+            let instruction_index = inner.index as usize;
+            let parsed = inner
+                .instructions
+                .iter()
+                .map(|inner| {
+                    let context = ParseContext {
+                        instruction: &inner.instruction,
+                        instruction_index,
+                        stack_height: inner.stack_height,
+                        instructions: &instructions,
+                        accounts,
+                        static_accounts_len: message.account_keys.len(),
+                        luts: message.luts.as_deref(),
+                        header: &message.header,
+                        version: message.transaction_type,
+                    };
+                    Self::parse_instruction(&context, &registry)
+                })
+                .collect::<Vec<Instruction>>();
+
+            message
+                .instructions
+                .get_mut(instruction_index)
+                .ok_or(SoleanaError::InvalidInstruction)?
+                .inner_instructions
+                .extend(parsed);
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolves the accounts of `luts` from the registered luts, like the message accounts are.
+fn registered_addresses(registry: &RegistryInner, luts: Option<&[LUT]>) -> LoadedAddresses {
+    let mut loaded = LoadedAddresses::default();
+    for lut in luts.unwrap_or_default() {
+        if let Some(accounts) = registry.luts.get(&lut.account_key) {
+            let resolve = |indexes: &[u8]| {
+                indexes
+                    .iter()
+                    .filter_map(|&i| accounts.get(i as usize).copied())
+                    .collect::<Vec<_>>()
+            };
+            loaded.writable.extend(resolve(&lut.writable_indexes));
+            loaded.readonly.extend(resolve(&lut.readonly_indexes));
+        }
+    }
+    loaded
+}
+
+/// Returns the bytes of an encoded transaction, `[data, encoding]` or a bare base58 string.
+fn transaction_bytes(json: &Json) -> SoleanaResult<Vec<u8>> {
+    match json {
+        Json::String(data) => base58::decode(data),
+        Json::Array(values) => match (values.first(), values.get(1).and_then(Json::as_str)) {
+            (Some(Json::String(data)), Some("base64")) => base64::decode(data),
+            (Some(Json::String(data)), Some("base58")) => base58::decode(data),
+            _ => Err(SoleanaError::InvalidJson),
+        },
+        // The `json` and `jsonParsed` encodings lose the serialized message.
+        _ => Err(SoleanaError::InvalidJson),
+    }
+}
+
+/// Parses the meta of a transaction, `accounts` are the accounts of the message followed by
+/// the loaded addresses.
+fn meta(
+    json: &Json,
+    accounts: &[Pubkey],
+    loaded_addresses: Option<LoadedAddresses>,
+) -> SoleanaResult<TransactionMeta> {
+    Ok(TransactionMeta {
+        err: optional(json, "err").map(transaction_error).transpose()?,
+        fee: u64_field(json, "fee")?,
+        pre_balances: u64s(field(json, "preBalances")?)?,
+        post_balances: u64s(field(json, "postBalances")?)?,
+        inner_instructions: list(json, "innerInstructions", |inner| {
+            inner_instructions(inner, accounts)
+        })?,
+        log_messages: optional(json, "logMessages")
+            .map(|logs| {
+                array(logs)?
+                    .iter()
+                    .map(|log| string(log).map(str::to_string))
+                    .collect()
+            })
+            .transpose()?,
+        pre_token_balances: list(json, "preTokenBalances", token_balance)?,
+        post_token_balances: list(json, "postTokenBalances", token_balance)?,
+        loaded_addresses,
+        compute_units_consumed: optional(json, "computeUnitsConsumed")
+            .map(|units| units.as_u64().ok_or(SoleanaError::InvalidJson))
+            .transpose()?,
+    })
+}
+
+/// Parses a `TransactionError`, serialized as its name or as `{ name: details }`.
+fn transaction_error(json: &Json) -> SoleanaResult<TransactionError> {
+    let (name, details) = error_variant(json)?;
+    match (name, details) {
+        ("InstructionError", Some(Json::Array(details))) => match &details[..] {
+            [index, error] => Ok(TransactionError::InstructionError(
+                u8_value(index)?,
+                instruction_error(error)?,
+            )),
+            _ => Err(SoleanaError::InvalidJson),
+        },
+        (name, _) => Ok(TransactionError::Other(name.to_string())),
+    }
+}
+
+fn instruction_error(json: &Json) -> SoleanaResult<InstructionError> {
+    match error_variant(json)? {
+        ("Custom", Some(code)) => code
+            .as_u64()
+            .and_then(|code| code.try_into().ok())
+            .map(InstructionError::Custom)
+            .ok_or(SoleanaError::InvalidJson),
+        (name, _) => Ok(InstructionError::Other(name.to_string())),
+    }
+}
+
+/// Splits a serialized enum variant into its name and its details, if any.
+fn error_variant(json: &Json) -> SoleanaResult<(&str, Option<&Json>)> {
+    match json {
+        Json::String(name) => Ok((name, None)),
+        Json::Object(fields) => match &fields[..] {
+            [(name, details)] => Ok((name, Some(details))),
+            _ => Err(SoleanaError::InvalidJson),
+        },
+        _ => Err(SoleanaError::InvalidJson),
+    }
+}
+
+/// Parses the inner instructions of a top-level instruction, resolving their program id from
+/// `accounts`.
+fn inner_instructions(json: &Json, accounts: &[Pubkey]) -> SoleanaResult<InnerInstructions> {
+    let instructions = array(field(json, "instructions")?)?
+        .iter()
+        .map(|instruction| {
+            let program_index = u8_value(field(instruction, "programIdIndex")?)?;
+            let program_id = *accounts
+                .get(program_index as usize)
+                .ok_or(SoleanaError::InvalidAccountIndex)?;
+
+            Ok(InnerInstruction {
+                instruction: RawInstruction {
+                    program_id,
+                    account_indexes: array(field(instruction, "accounts")?)?
+                        .iter()
+                        .map(u8_value)
+                        .collect::<SoleanaResult<_>>()?,
+                    data: base58::decode(string(field(instruction, "data")?)?)?,
+                },
+                stack_height: optional(instruction, "stackHeight")
+                    .map(|height| {
+                        height
+                            .as_u64()
+                            .and_then(|height| height.try_into().ok())
+                            .ok_or(SoleanaError::InvalidJson)
+                    })
+                    .transpose()?,
+            })
+        })
+        .collect::<SoleanaResult<_>>()?;
+
+    Ok(InnerInstructions {
+        index: u8_value(field(json, "index")?)?,
+        instructions,
+    })
+}
+
+fn token_balance(json: &Json) -> SoleanaResult<TokenBalance> {
+    let amount = field(json, "uiTokenAmount")?;
+    Ok(TokenBalance {
+        account_index: u8_value(field(json, "accountIndex")?)?,
+        mint: pubkey(field(json, "mint")?)?,
+        owner: optional(json, "owner").map(pubkey).transpose()?,
+        program_id: optional(json, "programId").map(pubkey).transpose()?,
+        // The raw amount is a string so it doesn't lose precision in JavaScript.
+        amount: string(field(amount, "amount")?)?
+            .parse()
+            .map_err(|_| SoleanaError::InvalidJson)?,
+        decimals: u8_value(field(amount, "decimals")?)?,
+    })
+}
+
+fn field<'a>(json: &'a Json, key: &str) -> SoleanaResult<&'a Json> {
+    json.get(key).ok_or(SoleanaError::InvalidJson)
+}
+
+/// Returns the value of `key`, `None` if it is missing or null.
+fn optional<'a>(json: &'a Json, key: &str) -> Option<&'a Json> {
+    json.get(key).filter(|value| !value.is_null())
+}
+
+/// Parses the array of `key` with `item`, a missing or null array is empty.
+fn list<T>(
+    json: &Json,
+    key: &str,
+    item: impl Fn(&Json) -> SoleanaResult<T>,
+) -> SoleanaResult<Vec<T>> {
+    match optional(json, key) {
+        Some(values) => array(values)?.iter().map(item).collect(),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn array(json: &Json) -> SoleanaResult<&[Json]> {
+    json.as_array().ok_or(SoleanaError::InvalidJson)
+}
+
+fn string(json: &Json) -> SoleanaResult<&str> {
+    json.as_str().ok_or(SoleanaError::InvalidJson)
+}
+
+fn u64_field(json: &Json, key: &str) -> SoleanaResult<u64> {
+    field(json, key)?.as_u64().ok_or(SoleanaError::InvalidJson)
+}
+
+fn u8_value(json: &Json) -> SoleanaResult<u8> {
+    json.as_u64()
+        .and_then(|value| value.try_into().ok())
+        .ok_or(SoleanaError::InvalidJson)
+}
+
+fn u64s(json: &Json) -> SoleanaResult<Vec<u64>> {
+    array(json)?
+        .iter()
+        .map(|value| value.as_u64().ok_or(SoleanaError::InvalidJson))
+        .collect()
+}
+
+fn pubkey(json: &Json) -> SoleanaResult<Pubkey> {
+    base58::decode_pubkey(string(json)?)
+}
+
+fn pubkeys(json: &Json) -> SoleanaResult<Vec<Pubkey>> {
+    array(json)?.iter().map(pubkey).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{programs::system::SystemInstructions, registry::Registry};
+
+    const V0_BASE64: &str = include_str!("../fixtures/rpc/v0_base64.json");
+    const LEGACY_BASE58: &str = include_str!("../fixtures/rpc/legacy_base58.json");
+
+    #[test]
+    fn test_parse_v0_transaction() {
+        // No lut is registered, the accounts come from the loaded addresses.
+        let parser = TransactionsParser::with_registry(Registry::builder().build());
+        let rpc = parser.parse_rpc_transaction(V0_BASE64).unwrap();
+
+        assert_eq!(rpc.slot, 370000000);
+        assert_eq!(rpc.block_time, Some(1760000000));
+
+        let message = &rpc.transaction.message;
+        assert_eq!(message.account_keys.len(), 4);
+        assert_eq!(message.instructions.len(), 3);
+        assert!(matches!(
+            message.instructions[1].parsed_as::<SystemInstructions>(),
+            Some(SystemInstructions::Transfer {
+                lamports: 1000000,
+                accounts,
+            }) if accounts.to == [0x0f; 32]
+        ));
+
+        let meta = rpc.meta.unwrap();
+        assert_eq!(meta.err, None);
+        assert_eq!(meta.fee, 5000);
+        assert_eq!(meta.pre_balances, [10000000, 1, 1, 1141440, 0]);
+        assert_eq!(meta.post_balances, [8995000, 1, 1, 1141440, 1005000]);
+        assert_eq!(meta.compute_units_consumed, Some(3250));
+        assert_eq!(meta.log_messages.unwrap().len(), 10);
+        assert_eq!(
+            meta.loaded_addresses,
+            Some(LoadedAddresses {
+                writable: vec![[0x0f; 32]],
+                readonly: vec![],
+            })
+        );
+
+        assert_eq!(
+            meta.inner_instructions,
+            [InnerInstructions {
+                index: 2,
+                instructions: vec![InnerInstruction {
+                    instruction: RawInstruction {
+                        program_id: [0; 32],
+                        account_indexes: vec![0, 4],
+                        data: [&2u32.to_le_bytes()[..], &5000u64.to_le_bytes()].concat(),
+                    },
+                    stack_height: Some(2),
+                }],
+            }]
+        );
+
+        let token_program =
+            base58::decode_pubkey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        assert_eq!(
+            meta.pre_token_balances,
+            [TokenBalance {
+                account_index: 4,
+                mint: [0x0d; 32],
+                owner: Some([0x0a; 32]),
+                program_id: Some(token_program),
+                amount: 2500000,
+                decimals: 6,
+            }]
+        );
+        assert_eq!(meta.post_token_balances[0].amount, 1500000);
+    }
+
+    #[test]
+    fn test_parse_inner_instructions() {
+        let parser = TransactionsParser::new();
+        let rpc = parser.parse_rpc_transaction(V0_BASE64).unwrap();
+        let instructions = &rpc.transaction.message.instructions;
+
+        assert!(instructions[..2]
+            .iter()
+            .all(|instruction| instruction.inner_instructions.is_empty()));
+        assert!(instructions
+            .iter()
+            .all(|instruction| instruction.stack_height == Some(1)));
+
+        // The transfer invoked by the unknown program is decoded like a top-level one.
+        let [transfer] = &instructions[2].inner_instructions[..] else {
+            panic!("expected one inner instruction");
+        };
+        assert_eq!(transfer.program_id, [0; 32]);
+        assert_eq!(transfer.stack_height, Some(2));
+        assert_eq!(transfer.name.as_deref(), Some("Transfer"));
+        assert!(matches!(
+            transfer.parsed_as::<SystemInstructions>(),
+            Some(SystemInstructions::Transfer {
+                lamports: 5000,
+                accounts,
+            }) if accounts.from == [0x0a; 32] && accounts.to == [0x0f; 32]
+        ));
+
+        // Inner instructions rejected by their parser are kept unparsed, like the System
+        // `CreateAccount` of every Anchor `init`.
+        let transfer_data = [&2u32.to_le_bytes()[..], &5000u64.to_le_bytes()].concat();
+        let create_account = [
+            &0u32.to_le_bytes()[..],
+            &5000u64.to_le_bytes(),
+            &165u64.to_le_bytes(),
+            &[0x0c; 32],
+        ]
+        .concat();
+        let unimplemented = V0_BASE64.replace(
+            &base58::encode(&transfer_data),
+            &base58::encode(&create_account),
+        );
+        assert_ne!(unimplemented, V0_BASE64);
+        let rpc = parser.parse_rpc_transaction(&unimplemented).unwrap();
+        let inner = &rpc.transaction.message.instructions[2].inner_instructions[0];
+        assert_eq!(inner.program_id, [0; 32]);
+        assert_eq!(inner.stack_height, Some(2));
+        assert_eq!(inner.name, None);
+        assert!(inner.parsed.is_none());
+        assert_eq!(inner.raw, create_account);
+
+        // Inner instructions must belong to a top-level instruction.
+        let orphan = V0_BASE64.replace(r#""index": 2"#, r#""index": 3"#);
+        assert_ne!(orphan, V0_BASE64);
+        assert_eq!(
+            parser.parse_rpc_transaction(&orphan),
+            Err(SoleanaError::InvalidInstruction)
+        );
+    }
+
+    #[test]
+    fn test_parse_legacy_transaction() {
+        let parser = TransactionsParser::new();
+        let rpc = parser.parse_rpc_transaction(LEGACY_BASE58).unwrap();
+
+        assert_eq!(rpc.slot, 370000001);
+        assert_eq!(rpc.block_time, None);
+        assert_eq!(rpc.transaction.signatures, [[0x01; 64]]);
+
+        let meta = rpc.meta.unwrap();
+        assert_eq!(
+            meta.err,
+            Some(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(1)
+            ))
+        );
+        assert_eq!(meta.loaded_addresses, None);
+        assert_eq!(meta.compute_units_consumed, None);
+        assert!(meta.inner_instructions.is_empty());
+
+        let error = parser.resolve_error(&[0; 32], 1).unwrap();
+        assert_eq!(error.name, "ResultWithNegativeLamports");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |err: &str| transaction_error(&Json::parse(err).unwrap());
+
+        assert_eq!(
+            parse(r#""AccountInUse""#),
+            Ok(TransactionError::Other("AccountInUse".to_string()))
+        );
+        assert_eq!(
+            parse(r#"{"InstructionError":[1,"InvalidAccountData"]}"#),
+            Ok(TransactionError::InstructionError(
+                1,
+                InstructionError::Other("InvalidAccountData".to_string())
+            ))
+        );
+        assert_eq!(
+            parse(r#"{"InstructionError":[0,{"BorshIoError":"Unknown"}]}"#),
+            Ok(TransactionError::InstructionError(
+                0,
+                InstructionError::Other("BorshIoError".to_string())
+            ))
+        );
+        assert_eq!(
+            parse(r#"{"InsufficientFundsForRent":{"account_index":2}}"#),
+            Ok(TransactionError::Other(
+                "InsufficientFundsForRent".to_string()
+            ))
+        );
+        assert_eq!(
+            parse(r#"{"InstructionError":[0]}"#),
+            Err(SoleanaError::InvalidJson)
+        );
+    }
+
+    #[test]
+    fn test_registered_luts_without_loaded_addresses() {
+        let without_loaded = V0_BASE64.replace(
+            r#""loadedAddresses": {
+        "readonly": [],
+        "writable": [
+          "21nS9Wz9sUTQ6MkcYUtnN8aSfPA26xJJP7zqshfzCzqc"
+        ]
+      },
+"#,
+            "",
+        );
+        assert_ne!(without_loaded, V0_BASE64);
+
+        let parser = TransactionsParser::with_registry(
+            Registry::builder()
+                .lut(([0x0e; 32], vec![[0x0f; 32]]))
+                .build(),
+        );
+        let rpc = parser.parse_rpc_transaction(&without_loaded).unwrap();
+        assert_eq!(rpc.meta.unwrap().loaded_addresses, None);
+
+        let instructions = &rpc.transaction.message.instructions;
+        assert!(matches!(
+            instructions[1].parsed_as::<SystemInstructions>(),
+            Some(SystemInstructions::Transfer { accounts, .. }) if accounts.to == [0x0f; 32]
+        ));
+        assert!(matches!(
+            instructions[2].inner_instructions[0].parsed_as::<SystemInstructions>(),
+            Some(SystemInstructions::Transfer { accounts, .. }) if accounts.to == [0x0f; 32]
+        ));
+    }
+
+    #[test]
+    fn test_rejects_bad_responses() {
+        let parser = TransactionsParser::new();
+
+        // The loaded addresses must match the lut indexes of the message.
+        let missing = V0_BASE64.replace(
+            r#""writable": [
+          "21nS9Wz9sUTQ6MkcYUtnN8aSfPA26xJJP7zqshfzCzqc"
+        ]"#,
+            r#""writable": []"#,
+        );
+        assert_ne!(missing, V0_BASE64);
+        assert_eq!(
+            parser.parse_rpc_transaction(&missing),
+            Err(SoleanaError::InvalidAccountIndex)
+        );
+
+        let json = V0_BASE64.replace(r#""base64""#, r#""jsonParsed""#);
+        assert_eq!(
+            parser.parse_rpc_transaction(&json),
+            Err(SoleanaError::InvalidJson)
+        );
+
+        assert_eq!(
+            parser.parse_rpc_transaction(r#"{"jsonrpc":"2.0","result":null,"id":1}"#),
+            Err(SoleanaError::InvalidJson)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+/// Size of a sha256 digest.
+pub const HASH_BYTES: usize = 32;
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Incremental sha256, usable in const contexts.
+///
+/// Equivalent to the `Hasher` from the solana-sha256-hasher. <https://docs.rs/solana-sha256-hasher/latest/solana_sha256_hasher/struct.Hasher.html>
+#[derive(Debug, Clone)]
+pub struct Hasher {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_len: usize,
+    len: u64,
+}
+
+impl Hasher {
+    /// Creates a hasher with no data.
+    pub const fn new() -> Self {
+        Self {
+            state: H0,
+            block: [0; 64],
+            block_len: 0,
+            len: 0,
+        }
+    }
+
+    /// Feeds `data` to the hasher.
+    pub const fn hash(&mut self, data: &[u8]) {
+        let mut i = 0;
+        while i < data.len() {
+            self.block[self.block_len] = data[i];
+            self.block_len += 1;
+            if self.block_len == 64 {
+                self.compress();
+                self.block_len = 0;
+            }
+            i += 1;
+        }
+        self.len += data.len() as u64;
+    }
+
+    /// Pads the data and returns the digest.
+    pub const fn result(mut self) -> [u8; HASH_BYTES] {
+        let bit_len = self.len.wrapping_mul(8);
+
+        self.block[self.block_len] = 0x80;
+        self.block_len += 1;
+        if self.block_len > 56 {
+            while self.block_len < 64 {
+                self.block[self.block_len] = 0;
+                self.block_len += 1;
+            }
+            self.compress();
+            self.block_len = 0;
+        }
+        while self.block_len < 56 {
+            self.block[self.block_len] = 0;
+            self.block_len += 1;
+        }
+
+        let len_bytes = bit_len.to_be_bytes();
+        let mut i = 0;
+        while i < 8 {
+            self.block[56 + i] = len_bytes[i];
+            i += 1;
+        }
+        self.compress();
+
+        let mut digest = [0u8; HASH_BYTES];
+        let mut i = 0;
+        while i < 8 {
+            let word = self.state[i].to_be_bytes();
+            digest[i * 4] = word[0];
+            digest[i * 4 + 1] = word[1];
+            digest[i * 4 + 2] = word[2];
+            digest[i * 4 + 3] = word[3];
+            i += 1;
+        }
+        digest
+    }
+
+    /// Processes the full block.
+    const fn compress(&mut self) {
+        let mut w = [0u32; 64];
+        let mut i = 0;
+        while i < 16 {
+            w[i] = u32::from_be_bytes([
+                self.block[i * 4],
+                self.block[i * 4 + 1],
+                self.block[i * 4 + 2],
+                self.block[i * 4 + 3],
+            ]);
+            i += 1;
+        }
+        while i < 64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+            i += 1;
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        let mut i = 0;
+        while i < 64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+            i += 1;
+        }
+
+        let words = [a, b, c, d, e, f, g, h];
+        let mut i = 0;
+        while i < 8 {
+            self.state[i] = self.state[i].wrapping_add(words[i]);
+            i += 1;
+        }
+    }
+}
+
+impl Default for Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the sha256 digest of the concatenation of `parts`.
+///
+/// Equivalent to the `hashv` from the solana-sha256-hasher. <https://docs.rs/solana-sha256-hasher/latest/solana_sha256_hasher/fn.hashv.html>
+pub const fn hashv(parts: &[&[u8]]) -> [u8; HASH_BYTES] {
+    let mut hasher = Hasher::new();
+    let mut i = 0;
+    while i < parts.len() {
+        hasher.hash(parts[i]);
+        i += 1;
+    }
+    hasher.result()
+}
+
+/// Returns the sha256 digest of `data`.
+///
+/// Equivalent to the `hash` from the solana-sha256-hasher. <https://docs.rs/solana-sha256-hasher/latest/solana_sha256_hasher/fn.hash.html>
+pub const fn hash(data: &[u8]) -> [u8; HASH_BYTES] {
+    hashv(&[data])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(
+            hex(&hash(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&hash(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Two blocks once padded.
+        assert_eq!(
+            hex(&hash(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(hashv(&[b"ab", b"", b"c"]), hash(b"abc"));
+        assert_eq!(hash(&[b'a'; 1000]), hashv(&[&[b'a'; 500], &[b'a'; 500]]));
+    }
+}
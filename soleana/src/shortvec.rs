@@ -0,0 +1,108 @@
+use crate::error::{SoleanaError, SoleanaResult};
+
+/// Maximum number of bytes of an encoded u16. <https://docs.rs/solana-short-vec/latest/solana_short_vec/constant.MAX_ENCODING_LENGTH.html>
+pub const MAX_ENCODING_LENGTH: usize = 3;
+
+/// Decodes a compact u16 from the start of `bytes`, returning the value and the number of bytes
+/// it took.
+///
+/// The encoding must be canonical, as enforced by the validator: a trailing zero byte (an alias
+/// of a shorter encoding) is rejected with [`SoleanaError::CompactU16NonCanonical`], a third byte
+/// with the continuation bit or a value above `u16::MAX` with [`SoleanaError::CompactU16Overflow`].
+///
+/// Equivalent to `decode_shortu16_len` from the solana-short-vec. <https://docs.rs/solana-short-vec/latest/solana_short_vec/fn.decode_shortu16_len.html>
+pub fn decode(bytes: &[u8]) -> SoleanaResult<(u16, usize)> {
+    let mut value: u32 = 0;
+
+    for nth_byte in 0..MAX_ENCODING_LENGTH {
+        let byte = *bytes.get(nth_byte).ok_or(SoleanaError::NotEnoughBytes)?;
+
+        if byte == 0 && nth_byte != 0 {
+            return Err(SoleanaError::CompactU16NonCanonical);
+        }
+
+        let done = byte & 0x80 == 0;
+        if nth_byte == MAX_ENCODING_LENGTH - 1 && !done {
+            return Err(SoleanaError::CompactU16Overflow);
+        }
+
+        value |= u32::from(byte & 0x7f) << (nth_byte * 7);
+        let value = u16::try_from(value).map_err(|_| SoleanaError::CompactU16Overflow)?;
+
+        if done {
+            return Ok((value, nth_byte + 1));
+        }
+    }
+
+    Err(SoleanaError::CompactU16Overflow)
+}
+
+/// Appends the canonical compact encoding of `value` to `out`.
+///
+/// Equivalent to `encode_length` from the solana-short-vec. <https://docs.rs/solana-short-vec/latest/solana_short_vec/fn.encode_length.html>
+pub fn encode_to(out: &mut Vec<u8>, value: u16) {
+    let mut rest = value;
+    loop {
+        let byte = (rest & 0x7f) as u8;
+        rest >>= 7;
+
+        if rest == 0 {
+            out.push(byte);
+            break;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
+
+/// Returns the canonical compact encoding of `value`.
+pub fn encode(value: u16) -> Vec<u8> {
+    let mut out = Vec::with_capacity(MAX_ENCODING_LENGTH);
+    encode_to(&mut out, value);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        for (value, bytes) in [
+            (0x0000, &[0x00][..]),
+            (0x007f, &[0x7f]),
+            (0x0080, &[0x80, 0x01]),
+            (0x00ff, &[0xff, 0x01]),
+            (0x0100, &[0x80, 0x02]),
+            (0x3fff, &[0xff, 0x7f]),
+            (0x4000, &[0x80, 0x80, 0x01]),
+            (0xffff, &[0xff, 0xff, 0x03]),
+        ] {
+            assert_eq!(encode(value), bytes);
+            assert_eq!(decode(bytes), Ok((value, bytes.len())));
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_non_canonical() {
+        assert_eq!(
+            decode(&[0x80, 0x00]),
+            Err(SoleanaError::CompactU16NonCanonical)
+        );
+        assert_eq!(
+            decode(&[0xff, 0x80, 0x00]),
+            Err(SoleanaError::CompactU16NonCanonical)
+        );
+        assert_eq!(
+            decode(&[0x80, 0x80, 0x04]),
+            Err(SoleanaError::CompactU16Overflow)
+        );
+        assert_eq!(
+            decode(&[0x80, 0x80, 0x80]),
+            Err(SoleanaError::CompactU16Overflow)
+        );
+        assert_eq!(decode(&[0x80]), Err(SoleanaError::NotEnoughBytes));
+        // Only the bytes of the value are consumed.
+        assert_eq!(decode(&[0x05, 0x00]), Ok((5, 1)));
+    }
+}
@@ -1,4 +1,9 @@
-use crate::programs::ProgramInstructions;
+use std::hash::Hasher;
+
+use crate::{
+    error::{SoleanaError, SoleanaResult},
+    programs::{system::System, Instructions, Program, ProgramInstructions},
+};
 
 /// Equivalent to the `Pubkey` from the solana-pubkey. <https://docs.rs/solana-pubkey/latest/solana_pubkey/struct.Pubkey.html>
 pub type Pubkey = [u8; 32];
@@ -6,24 +11,92 @@ pub type Pubkey = [u8; 32];
 /// Equivalent to the `Hash` from the solana-hash. <https://docs.rs/solana-hash/latest/solana_hash/struct.Hash.html>
 pub type Hash = [u8; 32];
 
+/// A signature slot that hasn't been signed yet.
+pub const EMPTY_SIGNATURE: Signature = [0; 64];
+
 /// Equivalent to the `Signature` from the solana-signature. <https://docs.rs/solana-signature/latest/solana_signature/struct.Signature.htmlhttps://docs.rs/solana-signature/latest/solana_signature/struct.Signature.html>
 pub type Signature = [u8; 64];
 
+/// The cluster a [`Registry`](crate::registry::Registry) parses transactions for, it selects the
+/// program ids the programs are registered under.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Cluster {
+    #[default]
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+    Custom(String),
+}
+
+/// An instruction as read from the message, before being parsed.
+///
+/// Equivalent to the `CompiledInstruction` from the solana-message, with the program id resolved. <https://docs.rs/solana-message/latest/solana_message/compiled_instruction/struct.CompiledInstruction.html>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawInstruction {
+    pub program_id: Pubkey,
+    /// Indexes of the instruction accounts into the account keys of the message.
+    pub account_indexes: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
 /// "Equivalent" to the 'Instruction' from solana-instruction. <https://docs.rs/solana-instruction/latest/solana_instruction/struct.Instruction.html>
 ///
 /// Every data fom solana-instruction::Instruction is included here. But not 100% equivalent since it implements more data.
-#[derive(Debug)]
+///
+/// Two instructions are equal if their raw and parsed contents are equal, the hash only covers the
+/// raw contents since the parsed instruction is decoded from them.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Instruction {
     /// The program ID of the instruction.
     pub program_id: Pubkey,
 
+    /// Indexes of the instruction accounts into the account keys of the message.
+    pub account_indexes: Vec<u8>,
+
+    /// The name of the instruction, from the parsed instruction or from the discriminators
+    /// registered for the program. Parsed instructions without a name fall back to the
+    /// registered names.
+    pub name: Option<String>,
+
     pub parsed: Option<Box<dyn ProgramInstructions>>,
 
     pub raw: Vec<u8>,
+
+    /// The invocation depth of the instruction: top-level instructions are at 1, the
+    /// instructions they invoke through CPIs at 2 and deeper. `None` for the inner instructions
+    /// whose depth wasn't recorded by the node, as older nodes don't.
+    pub stack_height: Option<u32>,
+
+    /// The instructions invoked through CPIs by this top-level instruction, in execution order.
+    ///
+    /// Only known from the meta of executed transactions, see
+    /// [`TransactionsParser::parse_rpc_transaction`](crate::TransactionsParser::parse_rpc_transaction).
+    pub inner_instructions: Vec<Instruction>,
+}
+
+impl std::hash::Hash for Instruction {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.program_id.hash(state);
+        self.account_indexes.hash(state);
+        self.raw.hash(state);
+    }
+}
+
+impl Instruction {
+    /// Returns the parsed instruction as `T`, if it was parsed into a `T`.
+    pub fn parsed_as<T: ProgramInstructions>(&self) -> Option<&T> {
+        self.parsed.as_deref()?.downcast_ref::<T>()
+    }
+
+    /// Consumes the instruction, returning the parsed instruction sorted into [`Instructions`].
+    pub fn into_instructions(self) -> Option<Instructions> {
+        self.parsed.map(Instructions::from)
+    }
 }
 
 /// Equivalent to the `MessageHeader` from the solana-message. <https://docs.rs/solana-message/latest/solana_message/struct.MessageHeader.html>
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Header {
     pub num_required_signatures: u8,
     pub num_readonly_signed_accounts: u8,
@@ -34,20 +107,45 @@ impl Header {
     pub(crate) const BYTE_SIZE: usize = 3;
 }
 
-#[derive(Debug)]
+/// The version of a transaction message.
+///
+/// Versioned messages are prefixed by a byte with the high bit set, the remaining 7 bits are
+/// the version number. Legacy messages have no prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Indicator {
     Legacy,
     V0,
 }
 
-#[derive(Debug)]
+impl Indicator {
+    /// Bit set on the first byte of a versioned message.
+    pub(crate) const VERSION_PREFIX: u8 = 0x80;
+
+    /// Creates an [`Indicator`] from a version number, failing for versions we can't parse.
+    pub fn from_version(version: u8) -> SoleanaResult<Self> {
+        match version {
+            0 => Ok(Indicator::V0),
+            n => Err(SoleanaError::UnsupportedVersion(n)),
+        }
+    }
+
+    /// Returns the version number, `None` for legacy messages.
+    pub fn version(&self) -> Option<u8> {
+        match self {
+            Indicator::Legacy => None,
+            Indicator::V0 => Some(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LUT {
     pub account_key: Pubkey,
     pub writable_indexes: Vec<u8>,
     pub readonly_indexes: Vec<u8>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CompleteAddressLookupTable {
     pub account_key: Pubkey,
     pub accounts: Vec<Pubkey>,
@@ -62,12 +160,179 @@ impl From<(Pubkey, Vec<Pubkey>)> for CompleteAddressLookupTable {
     }
 }
 
-#[derive(Debug)]
-pub struct Transaction {
+/// Equivalent to the `VersionedMessage` from the solana-message. <https://docs.rs/solana-message/latest/solana_message/enum.VersionedMessage.html>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Message {
     pub transaction_type: Indicator,
-    pub signatures: Vec<Signature>,
     pub header: Header,
+    /// The static account keys of the message, without the ones loaded from the luts.
+    pub account_keys: Vec<Pubkey>,
     pub hash: Hash,
     pub instructions: Vec<Instruction>,
     pub luts: Option<Vec<LUT>>,
 }
+
+/// Equivalent to the `VersionedTransaction` from the solana-transaction. <https://docs.rs/solana-transaction/latest/solana_transaction/versioned/struct.VersionedTransaction.html>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Transaction {
+    pub signatures: Vec<Signature>,
+    pub message: Message,
+}
+
+/// What the `hash` of a message stands for, which decides how the transaction expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransactionLifetime {
+    /// A recent blockhash, the transaction expires once it is too old.
+    RecentBlockhash(Hash),
+    /// The value stored in a nonce account, the transaction is valid until the nonce is advanced.
+    DurableNonce(DurableNonce),
+}
+
+/// The durable nonce of a transaction whose first instruction is a System `AdvanceNonceAccount`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DurableNonce {
+    pub nonce: Hash,
+    pub nonce_account: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl Message {
+    /// Returns the durable nonce if the first instruction is a System `AdvanceNonceAccount`.
+    ///
+    /// Like the validator, the nonce account must be a writable static account key, nonce
+    /// accounts loaded from a lut are rejected. The authority is resolved from the static
+    /// account keys too, it has to sign the transaction anyway.
+    pub fn durable_nonce(&self) -> Option<DurableNonce> {
+        let instruction = self.instructions.first()?;
+
+        if instruction.program_id != System::program_id()
+            || instruction.raw.get(0..4) != Some(&[0x04, 0x00, 0x00, 0x00])
+        {
+            return None;
+        }
+
+        let nonce_index = *instruction.account_indexes.first()? as usize;
+        let num_signers = self.header.num_required_signatures as usize;
+        let is_writable = if nonce_index < num_signers {
+            nonce_index
+                < num_signers.saturating_sub(self.header.num_readonly_signed_accounts as usize)
+        } else {
+            nonce_index
+                < self
+                    .account_keys
+                    .len()
+                    .saturating_sub(self.header.num_readonly_unsigned_accounts as usize)
+        };
+        if !is_writable {
+            return None;
+        }
+
+        let account = |i: usize| {
+            instruction
+                .account_indexes
+                .get(i)
+                .and_then(|&index| self.account_keys.get(index as usize))
+                .copied()
+        };
+
+        Some(DurableNonce {
+            nonce: self.hash,
+            nonce_account: account(0)?,
+            authority: account(2)?,
+        })
+    }
+
+    /// Returns the `hash` of the message, tagged with what it stands for.
+    pub fn lifetime(&self) -> TransactionLifetime {
+        match self.durable_nonce() {
+            Some(nonce) => TransactionLifetime::DurableNonce(nonce),
+            None => TransactionLifetime::RecentBlockhash(self.hash),
+        }
+    }
+
+    /// Returns `true` if both messages are the same message, and so share the same signers.
+    pub fn is_same_message(&self, other: &Message) -> bool {
+        self.transaction_type == other.transaction_type
+            && self.header == other.header
+            && self.account_keys == other.account_keys
+            && self.hash == other.hash
+            && self.luts == other.luts
+            && self.instructions.len() == other.instructions.len()
+            && self
+                .instructions
+                .iter()
+                .zip(&other.instructions)
+                .all(|(a, b)| {
+                    a.program_id == b.program_id
+                        && a.account_indexes == b.account_indexes
+                        && a.raw == b.raw
+                })
+    }
+}
+
+impl Transaction {
+    /// Returns the durable nonce if the first instruction is a System `AdvanceNonceAccount`.
+    pub fn durable_nonce(&self) -> Option<DurableNonce> {
+        self.message.durable_nonce()
+    }
+
+    /// Returns the `hash` of the transaction, tagged with what it stands for.
+    pub fn lifetime(&self) -> TransactionLifetime {
+        self.message.lifetime()
+    }
+
+    /// Returns every signer pubkey with the signature in its slot.
+    ///
+    /// The signers are the first `num_required_signatures` account keys, in the same order as the signatures.
+    pub fn signers(&self) -> impl Iterator<Item = (&Pubkey, &Signature)> {
+        self.message
+            .account_keys
+            .iter()
+            .take(self.message.header.num_required_signatures as usize)
+            .zip(&self.signatures)
+    }
+
+    /// Returns the signers whose signature slot is still empty.
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        self.signers()
+            .filter(|(_, signature)| **signature == EMPTY_SIGNATURE)
+            .map(|(signer, _)| *signer)
+            .collect()
+    }
+
+    /// Returns `true` if every signer has signed the transaction.
+    pub fn is_fully_signed(&self) -> bool {
+        self.signatures.len() == self.message.header.num_required_signatures as usize
+            && self.signatures.iter().all(|s| *s != EMPTY_SIGNATURE)
+    }
+
+    /// Merges the signatures of another partially signed copy of the same message.
+    ///
+    /// Empty slots are filled from `other`, slots signed in both copies must hold the same signature.
+    pub fn merge_signatures(&mut self, other: &Transaction) -> SoleanaResult<()> {
+        if self.signatures.len() != other.signatures.len()
+            || !self.message.is_same_message(&other.message)
+        {
+            return Err(SoleanaError::MessageMismatch);
+        }
+
+        for (index, (signature, other)) in self
+            .signatures
+            .iter_mut()
+            .zip(&other.signatures)
+            .enumerate()
+        {
+            if *other == EMPTY_SIGNATURE || signature == other {
+                continue;
+            }
+
+            if *signature != EMPTY_SIGNATURE {
+                return Err(SoleanaError::SignatureConflict(index));
+            }
+
+            *signature = *other;
+        }
+
+        Ok(())
+    }
+}
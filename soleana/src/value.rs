@@ -0,0 +1,111 @@
+use crate::types::Pubkey;
+
+/// A dynamic representation of the fields of a parsed instruction.
+///
+/// Lets explorers and exporters walk the fields of any [`ProgramInstructions`](crate::programs::ProgramInstructions)
+/// without knowing its concrete type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    F32(f32),
+    F64(f64),
+    Pubkey(Pubkey),
+    Bytes(Vec<u8>),
+    String(String),
+    /// Named fields, in declaration order.
+    Struct(Vec<(String, Value)>),
+    Array(Vec<Value>),
+    Option(Option<Box<Value>>),
+    /// An enum variant with its fields.
+    Enum(String, Box<Value>),
+}
+
+impl Value {
+    /// Creates an empty [`Value::Struct`].
+    pub fn empty() -> Self {
+        Value::Struct(Vec::new())
+    }
+
+    /// Returns the field `name` of a [`Value::Struct`].
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+/// A trait for values that can be converted into a [`Value`].
+pub trait ToValue {
+    fn to_value(&self) -> Value;
+}
+
+macro_rules! impl_to_value {
+    ($($ty:ty => $variant:ident),*) => {
+        $(
+            impl ToValue for $ty {
+                fn to_value(&self) -> Value {
+                    Value::$variant(*self)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_value!(
+    bool => Bool, u8 => U8, u16 => U16, u32 => U32, u64 => U64, u128 => U128,
+    i8 => I8, i16 => I16, i32 => I32, i64 => I64, i128 => I128, f32 => F32, f64 => F64
+);
+
+impl ToValue for String {
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    fn to_value(&self) -> Value {
+        Value::Option(self.as_ref().map(|value| Box::new(value.to_value())))
+    }
+}
+
+impl<T: ToValue> ToValue for Vec<T> {
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(ToValue::to_value).collect())
+    }
+}
+
+impl<T: ToValue, const N: usize> ToValue for [T; N] {
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(ToValue::to_value).collect())
+    }
+}
+
+/// An account of a parsed instruction, labeled with its name in the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabeledAccount {
+    pub name: String,
+    pub pubkey: Pubkey,
+}
+
+impl LabeledAccount {
+    pub fn new(name: impl Into<String>, pubkey: Pubkey) -> Self {
+        Self {
+            name: name.into(),
+            pubkey,
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use soleana::{prelude::program_impl::*, registry::Registry, TransactionsParser};
+use soleana_derive::{Accounts, Program};
+
+#[derive(Debug, Clone, PartialEq, Eq, Accounts)]
+struct VaultAccounts {
+    owner: Pubkey,
+    vault: Pubkey,
+    referrer: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Program)]
+#[program(id = "6LtLpnUFNByNXLyCoK9wA2MykKAmQNZKBdY8s47dehDc", name = "Vault")]
+enum VaultInstructions {
+    #[discriminator(2, 0, 0, 0)]
+    Withdraw {
+        amount: u64,
+        #[accounts]
+        accounts: VaultAccounts,
+    },
+    #[anchor("deposit")]
+    Deposit {
+        amount: u64,
+        memo: Option<String>,
+        destination: Pubkey,
+    },
+    #[discriminator([9])]
+    Close,
+    #[discriminator([10])]
+    Route {
+        hops: Vec<Pubkey>,
+        pair: [Pubkey; 2],
+        fallback: Option<Pubkey>,
+        seed: [u8; 4],
+    },
+}
+
+/// Builds a transaction calling the vault with `instructions`, the accounts are
+/// `[0x0a; 32]`, `[0x0b; 32]` and the vault program.
+fn transaction(instructions: &[String]) -> String {
+    let program_id: String = <VaultInstructions as Program>::program_id()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    format!(
+        "01{}{}{}{}{:02x}{}",
+        "00".repeat(64),
+        "01000103",
+        "0a".repeat(32) + &"0b".repeat(32) + &program_id,
+        "00".repeat(32),
+        instructions.len(),
+        instructions.concat(),
+    )
+}
+
+#[test]
+fn test_derive_program() {
+    let parser = TransactionsParser::with_registry(
+        Registry::builder().program::<VaultInstructions>().build(),
+    );
+
+    let withdraw = format!("0203{}{}", "000102", "0c02000000e803000000000000");
+    let withdraw_without_vault = format!("0201{}{}", "00", "0c02000000e803000000000000");
+    let withdraw_without_amount = format!("0203{}{}", "000102", "0402000000");
+    let deposit = format!(
+        "0200{}{}{}",
+        "37",
+        "f223c68952e1f2b6",
+        "0500000000000000".to_owned() + "01020000006869" + &"0b".repeat(32),
+    );
+    let close = "02000109".to_string();
+
+    let parsed = parser
+        .parse_transaction(&transaction(&[withdraw, deposit, close]))
+        .unwrap();
+    let instructions = &parsed.message.instructions;
+
+    let withdraw = instructions[0].parsed_as::<VaultInstructions>().unwrap();
+    assert_eq!(
+        withdraw,
+        &VaultInstructions::Withdraw {
+            amount: 1000,
+            accounts: VaultAccounts {
+                owner: [0x0a; 32],
+                vault: [0x0b; 32],
+                referrer: None,
+            },
+        }
+    );
+    assert_eq!(withdraw.program_name(), "Vault");
+    assert_eq!(withdraw.instruction_name(), "Withdraw");
+    assert_eq!(
+        withdraw.accounts(),
+        vec![
+            LabeledAccount::new("owner", [0x0a; 32]),
+            LabeledAccount::new("vault", [0x0b; 32]),
+        ]
+    );
+    assert_eq!(withdraw.fields().get("amount"), Some(&Value::U64(1000)));
+
+    let deposit = instructions[1].parsed_as::<VaultInstructions>().unwrap();
+    assert_eq!(
+        deposit,
+        &VaultInstructions::Deposit {
+            amount: 5,
+            memo: Some("hi".to_string()),
+            destination: [0x0b; 32],
+        }
+    );
+    assert_eq!(
+        deposit.fields().get("destination"),
+        Some(&Value::Pubkey([0x0b; 32]))
+    );
+    assert!(deposit.accounts().is_empty());
+
+    assert_eq!(
+        instructions[2].parsed_as::<VaultInstructions>(),
+        Some(&VaultInstructions::Close)
+    );
+
+    // Instructions missing data or accounts are left unparsed instead of panicking.
+    let parsed = parser
+        .parse_transaction(&transaction(&[
+            withdraw_without_vault,
+            withdraw_without_amount,
+        ]))
+        .unwrap();
+    assert!(parsed
+        .message
+        .instructions
+        .iter()
+        .all(|instruction| instruction.parsed.is_none()));
+}
+
+#[test]
+fn test_derive_rejects_unknown_and_trailing_data() {
+    let parser = TransactionsParser::with_registry(
+        Registry::builder().program::<VaultInstructions>().build(),
+    );
+
+    let close = "02000109".to_string();
+    let close_with_trailing_bytes = "0200020900".to_string();
+    let unknown = "0200010b".to_string();
+
+    let parsed = parser
+        .parse_transaction(&transaction(&[close, close_with_trailing_bytes, unknown]))
+        .unwrap();
+    let parsed: Vec<_> = parsed
+        .message
+        .instructions
+        .iter()
+        .map(|instruction| instruction.parsed_as::<VaultInstructions>())
+        .collect();
+    assert_eq!(parsed, [Some(&VaultInstructions::Close), None, None]);
+}
+
+#[test]
+fn test_derive_nested_pubkeys() {
+    let route = VaultInstructions::Route {
+        hops: vec![[0x0a; 32]],
+        pair: [[0x0b; 32], [0x0c; 32]],
+        fallback: Some([0x0d; 32]),
+        seed: [1, 2, 3, 4],
+    };
+
+    let fields = route.fields();
+    assert_eq!(
+        fields.get("hops"),
+        Some(&Value::Array(vec![Value::Pubkey([0x0a; 32])]))
+    );
+    assert_eq!(
+        fields.get("pair"),
+        Some(&Value::Array(vec![
+            Value::Pubkey([0x0b; 32]),
+            Value::Pubkey([0x0c; 32])
+        ]))
+    );
+    assert_eq!(
+        fields.get("fallback"),
+        Some(&Value::Option(Some(Box::new(Value::Pubkey([0x0d; 32])))))
+    );
+    assert_eq!(
+        fields.get("seed"),
+        Some(&Value::Array(vec![
+            Value::U8(1),
+            Value::U8(2),
+            Value::U8(3),
+            Value::U8(4)
+        ]))
+    );
+}